@@ -69,6 +69,12 @@ pub enum Command<'a> {
     InstallTool {
         config: ToolInstallConfig,
     },
+    CheckAppConflicts {
+        config: AppInstallConfig,
+    },
+    CheckToolConflicts {
+        config: ToolInstallConfig,
+    },
     UninstallApp {
         id: String,
     },
@@ -81,6 +87,16 @@ pub enum Command<'a> {
     RepairTool {
         id: String,
     },
+    VerifyApp {
+        id: String,
+        #[serde(default)]
+        fast: bool,
+    },
+    VerifyTool {
+        id: String,
+        #[serde(default)]
+        fast: bool,
+    },
     ReinstallApp {
         id: String,
         zip_path: &'a str,
@@ -112,6 +128,11 @@ pub enum Command<'a> {
     GetDetails {
         path: ExePath,
     },
+    GetDetailsBatch {
+        zip_path: &'a str,
+        executable_paths: Vec<String>,
+        password: Option<&'a str>,
+    },
     RunInstaller {
         path: ExePath,
     },
@@ -131,6 +152,43 @@ pub enum Command<'a> {
     GetArchiveTree {
         path: &'a str,
         password: Option<&'a str>,
+        max_depth: Option<u32>,
+    },
+    GetArchiveSubtree {
+        path: &'a str,
+        password: Option<&'a str>,
+        subtree: &'a str,
+        max_depth: Option<u32>,
+    },
+    SuggestExecutables {
+        path: &'a str,
+        password: Option<&'a str>,
+    },
+    ClassifyPackage {
+        path: &'a str,
+        password: Option<&'a str>,
+    },
+    SearchArchive {
+        path: &'a str,
+        password: Option<&'a str>,
+        query: &'a str,
+    },
+    TestArchive {
+        path: &'a str,
+        password: Option<&'a str>,
+    },
+    ResolveArchivePassword {
+        path: &'a str,
+        password: Option<&'a str>,
+        source: Option<&'a str>,
+    },
+    ClearPasswordVault,
+    CheckExtractionPolicy {
+        path: &'a str,
+        password: Option<&'a str>,
+        destination: &'a str,
+        #[serde(default)]
+        filter: ExtractionFilter,
     },
     GetTimestamp,
     DetermineInputType {
@@ -200,15 +258,23 @@ impl<'a> Command<'a> {
             InstallTool { config } => {
                 json!(install_tool(config, &app).await?)
             }
+            CheckAppConflicts { config } => {
+                json!(check_install_conflicts(InstallRequest::App(&config)).await?)
+            }
+            CheckToolConflicts { config } => {
+                json!(check_install_conflicts(InstallRequest::Tool(&config)).await?)
+            }
             UninstallApp { id } => json!(uninstall_app(&id).await?),
             UninstallTool { id } => json!(uninstall_tool(&id).await?),
             RepairApp { id } => json!(repair_app(&id).await?),
             RepairTool { id } => json!(repair_tool(&id).await?),
+            VerifyApp { id, fast } => json!(verify_app(&id, fast).await?),
+            VerifyTool { id, fast } => json!(verify_tool(&id, fast).await?),
             ReinstallApp { id, zip_path } => {
-                json!(reinstall_app(&id, zip_path).await?)
+                json!(reinstall_app(&id, zip_path, &app).await?)
             }
             ReinstallTool { id, zip_path } => {
-                json!(reinstall_tool(&id, zip_path).await?)
+                json!(reinstall_tool(&id, zip_path, &app).await?)
             }
             ModifyApp { new_app, id } => {
                 json!(modify_app(new_app, &id).await?)
@@ -225,6 +291,11 @@ impl<'a> Command<'a> {
             } => json!(open_registry(app_name, current_user_only).await?),
 
             GetDetails { path } => json!(get_details(path).await?),
+            GetDetailsBatch {
+                zip_path,
+                executable_paths,
+                password,
+            } => json!(get_details_batch(zip_path, executable_paths, password).await?),
             RunInstaller { path } => json!(
                 run_installer(
                     &path.zip_path,
@@ -240,9 +311,45 @@ impl<'a> Command<'a> {
                 json!(get_archive_content(path, password).await?)
             }
             CheckPathEmpty { path } => json!(check_path_empty(path).await?),
-            GetArchiveTree { path, password } => {
-                json!(get_archive_tree(path, password).await?)
+            GetArchiveTree {
+                path,
+                password,
+                max_depth,
+            } => json!(get_archive_tree(path, password, max_depth).await?),
+            GetArchiveSubtree {
+                path,
+                password,
+                subtree,
+                max_depth,
+            } => json!(get_archive_subtree(path, password, subtree, max_depth).await?),
+            SuggestExecutables { path, password } => {
+                json!(suggest_executables(path, password).await?)
+            }
+            ClassifyPackage { path, password } => {
+                json!(classify_package(path, password).await?)
+            }
+            SearchArchive {
+                path,
+                password,
+                query,
+            } => json!(search_archive(path, password, query).await?),
+            TestArchive { path, password } => json!(test_archive(path, password).await?),
+            ResolveArchivePassword {
+                path,
+                password,
+                source,
+            } => {
+                // Archives opened without a download URL are ranked by their folder
+                let source = source.filter(|source| !source.is_empty()).unwrap_or(path);
+                json!(resolve_archive_password(path, password, source).await?)
             }
+            ClearPasswordVault => json!(Vault::clear().await?),
+            CheckExtractionPolicy {
+                path,
+                password,
+                destination,
+                filter,
+            } => json!(check_extraction_policy(path, password, destination, &filter).await?),
             GetTimestamp => json!(chrono::Utc::now().to_rfc3339()),
             DetermineInputType { input } => json!(determine_input_type(input).await?),
             CheckForUpdates => {
@@ -1,3 +1,5 @@
+use crate::operations::ExtractionFilter;
+use crate::utils::pe::SignatureInfo;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -34,6 +36,11 @@ pub struct AppDetails {
     pub config: AppConfig,
     pub install_path: String,
     pub full_path: String,
+    // What the Authenticode blob of the main executable claimed at install time. The signer's
+    // signature is not checked, so this names a publisher without vouching for it.
+    pub unverified_signature: Option<SignatureInfo>,
+    // The selection installed from the archive, applied again on reinstall
+    pub filter: ExtractionFilter,
 }
 
 #[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
@@ -88,6 +95,8 @@ pub struct ToolDetails {
     pub name: String,
     pub add_to_path: (bool, String),
     pub install_path: String,
+    // The selection installed from the archive, applied again on reinstall
+    pub filter: ExtractionFilter,
 }
 
 #[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
@@ -1,6 +1,7 @@
 pub mod env;
 pub mod library;
 pub mod settings;
+pub mod vault;
 
 use anyhow::Result;
 #[allow(ambiguous_glob_reexports)]
@@ -11,6 +12,8 @@ use serde::{Serialize, de::DeserializeOwned};
 #[allow(ambiguous_glob_reexports)]
 pub use settings::*;
 use std::path::PathBuf;
+#[allow(ambiguous_glob_reexports)]
+pub use vault::*;
 
 #[async_trait::async_trait]
 pub trait ConfigFile: DeserializeOwned + Serialize + Default + Clone + Send + 'static {
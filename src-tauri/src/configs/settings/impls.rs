@@ -1,6 +1,9 @@
-use super::{AppInstall, InstallSettings, LanguageType, Settings, ThemeType, ToolInstall};
+use super::{
+    AppInstall, ArchiveSettings, InstallSettings, LanguageType, Settings, ThemeType, ToolInstall,
+};
 use crate::configs::{ConfigFile, env::Env};
 use crate::core::{context_menu, startup, theme};
+use crate::operations::ExtractionPolicy;
 use anyhow::{Result, anyhow};
 use std::path::PathBuf;
 
@@ -54,6 +57,18 @@ impl Default for Settings {
                     .unwrap_or("C:\\".to_owned()),
                 add_to_path: true,
             },
+            archive: ArchiveSettings::default(),
+        }
+    }
+}
+
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        Self {
+            unwrap_depth: 3,
+            test_before_install: false,
+            extraction_policy: ExtractionPolicy::default(),
+            cache_size_limit_mb: 4096,
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::operations::ExtractionPolicy;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -15,6 +16,7 @@ pub struct Settings {
     pub run_as_admin: bool,
     pub app_install: AppInstall,
     pub tool_install: ToolInstall,
+    pub archive: ArchiveSettings,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
@@ -82,3 +84,15 @@ pub struct ToolInstall {
     pub install_path: String,
     pub add_to_path: bool,
 }
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct ArchiveSettings {
+    pub unwrap_depth: u32,
+    pub test_before_install: bool,
+    pub extraction_policy: ExtractionPolicy,
+    // Extracted archives are kept in the temp folder up to this size
+    #[ts(type = "number")]
+    pub cache_size_limit_mb: u64,
+}
@@ -0,0 +1,128 @@
+use super::{Vault, VaultEntry};
+use crate::configs::ConfigFile;
+use crate::utils::crypto::{decrypt_data_with_key, encrypt_data_with_key, generate_session_key};
+use anyhow::{Result, anyhow};
+use std::path::PathBuf;
+
+#[async_trait::async_trait]
+impl ConfigFile for Vault {
+    fn get_file_path() -> Result<PathBuf> {
+        Ok(dirs::config_local_dir()
+            .ok_or_else(|| anyhow!("Failed to get local config directory"))?
+            .join("AppPorter")
+            .join("Vault.json"))
+    }
+}
+
+impl Vault {
+    // The key lives beside the vault, which keeps passwords out of plain-text
+    // config files and backups without claiming to stop a local attacker
+    async fn key() -> Result<[u8; 32]> {
+        let key_path = Self::get_file_path()?.with_file_name("Vault.key");
+        if let Ok(bytes) = tokio::fs::read(&key_path).await
+            && let Ok(key) = <[u8; 32]>::try_from(bytes.as_slice())
+        {
+            return Ok(key);
+        }
+
+        let key = generate_session_key();
+        if let Some(parent) = key_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&key_path, key).await?;
+        Ok(key)
+    }
+
+    // Saved passwords, those used for archives from the same place as `source` first,
+    // then most recently used first
+    pub async fn passwords(&self, source: &str) -> Result<Vec<String>> {
+        let key = Self::key().await?;
+        let place = source_place(source);
+        let mut entries: Vec<&VaultEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| {
+            let a_local = place.is_some() && source_place(&a.source) == place;
+            let b_local = place.is_some() && source_place(&b.source) == place;
+            b_local
+                .cmp(&a_local)
+                .then_with(|| b.last_used.cmp(&a.last_used))
+        });
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|entry| decrypt_data_with_key(&entry.password, &entry.nonce, &key).ok())
+            .collect())
+    }
+
+    // Adds a password or marks an existing one as just used
+    pub async fn remember(&mut self, password: &str, source: &str) -> Result<()> {
+        if password.is_empty() {
+            return Ok(());
+        }
+
+        let key = Self::key().await?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let existing = self.entries.iter_mut().find(|entry| {
+            decrypt_data_with_key(&entry.password, &entry.nonce, &key)
+                .is_ok_and(|saved| saved == password)
+        });
+
+        match existing {
+            Some(entry) => {
+                entry.last_used = now;
+                if !source.is_empty() {
+                    entry.source = source.to_owned();
+                }
+            }
+            None => {
+                let (password, nonce) = encrypt_data_with_key(password, &key)?;
+                self.entries.push(VaultEntry {
+                    password,
+                    nonce,
+                    source: source.to_owned(),
+                    last_used: now,
+                });
+            }
+        }
+
+        self.save().await
+    }
+
+    pub async fn clear() -> Result<()> {
+        Vault::default().save().await
+    }
+}
+
+// The host of a download URL or the folder of a local archive
+fn source_place(source: &str) -> Option<String> {
+    let source = source.trim().to_lowercase();
+    if let Some((_, rest)) = source.split_once("://") {
+        let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        return (!host.is_empty()).then(|| host.to_owned());
+    }
+    let folder = source.replace('/', "\\");
+    let (folder, _) = folder.rsplit_once('\\')?;
+    (!folder.is_empty()).then(|| folder.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn places_urls_by_host_and_paths_by_folder() {
+        assert_eq!(
+            source_place("https://Example.com/files/app.zip?dl=1").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            source_place("C:\\Downloads\\App.7z").as_deref(),
+            Some("c:\\downloads")
+        );
+        assert_eq!(
+            source_place("C:/Downloads/tool.zip"),
+            source_place("c:\\downloads\\other.rar")
+        );
+        assert_eq!(source_place(""), None);
+        assert_eq!(source_place("app.zip"), None);
+    }
+}
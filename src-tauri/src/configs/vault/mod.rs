@@ -0,0 +1,4 @@
+pub mod impls;
+pub mod structs;
+
+pub use structs::*;
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+// Saved archive passwords. Never sent to the frontend as a whole.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Vault {
+    pub entries: Vec<VaultEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct VaultEntry {
+    pub password: String,
+    pub nonce: String,
+    pub source: String,
+    pub last_used: String,
+}
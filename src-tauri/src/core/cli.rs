@@ -1,4 +1,4 @@
-use crate::SUPPORTED_EXTENSIONS;
+use crate::operations::is_archive_file;
 use anyhow::Result;
 use lazy_static::lazy_static;
 use tauri::{AppHandle, Emitter};
@@ -35,13 +35,8 @@ pub async fn cli(app: &AppHandle) -> Result<()> {
         match args[1].as_str() {
             "preview" => {
                 let value = &args[2];
-                if let Some(extension) = std::path::Path::new(&value)
-                    .extension()
-                    .and_then(|ext| ext.to_str())
-                {
-                    if SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
-                        app.emit("preview", (value, Uuid::new_v4().to_string()))?;
-                    }
+                if is_archive_file(std::path::Path::new(&value)) {
+                    app.emit("preview", (value, Uuid::new_v4().to_string()))?;
                 }
             }
             "uninstall_app" => {
@@ -1,4 +1,4 @@
-use crate::operations::preview_url;
+use crate::operations::{ensure_archive_extension, preview_url};
 use crate::utils::crypto::*;
 use crate::utils::download_file;
 use anyhow::{Result, anyhow};
@@ -115,7 +115,8 @@ async fn handle_extension_message(app: &AppHandle, msg: Message) -> Result<Messa
                 decrypt_data_with_key(encrypted_data, nonce_str, &session_key)
             {
                 let timestamp = chrono::Utc::now().timestamp();
-                let downloaded = download_file(&decrypted_url).await?;
+                let downloaded =
+                    ensure_archive_extension(&download_file(&decrypted_url).await?).await?;
                 app.emit("preview_url", (downloaded, timestamp, decrypted_url))?;
 
                 // Return encrypted success response using session key
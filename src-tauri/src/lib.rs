@@ -4,4 +4,5 @@ pub mod core;
 pub mod operations;
 pub mod utils;
 
-pub const SUPPORTED_EXTENSIONS: [&str; 8] = ["zip", "7z", "rar", "tar", "gz", "bz2", "xz", "cab"];
+pub const SUPPORTED_EXTENSIONS: [&str; 9] =
+    ["zip", "7z", "rar", "tar", "gz", "bz2", "xz", "cab", "001"];
@@ -1,7 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use anyhow::Result;
-use app_porter_lib::{commands, configs::*, core::*};
+use app_porter_lib::{commands, configs::*, core::*, operations::InstallJournal};
 use tauri::Manager;
 
 #[tokio::main]
@@ -30,6 +30,13 @@ async fn run() -> Result<()> {
                 window.set_focus()?;
             }
 
+            // Only the primary instance gets here, a second launch has already been handed over
+            tokio::spawn(async move {
+                if let Err(e) = InstallJournal::recover().await {
+                    eprintln!("Install rollback error: {e}");
+                }
+            });
+
             let handle = app.handle().clone();
             tokio::spawn(async move {
                 if let Err(e) = start_websocket_server(&handle).await {
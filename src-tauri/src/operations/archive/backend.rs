@@ -0,0 +1,102 @@
+#[cfg(windows)]
+use super::SevenZipBackend;
+use super::{
+    ArchiveEntry, ArchiveTestReport, EntryFilter, NativeBackend, ProgressReporter, VolumeSet,
+    sniff_file,
+};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    SevenZip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarBz2,
+    Other,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Self::TarGz
+        } else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+            Self::TarXz
+        } else if name.ends_with(".tar.bz2") || name.ends_with(".tbz2") || name.ends_with(".tbz") {
+            Self::TarBz2
+        } else if name.ends_with(".tar") {
+            Self::Tar
+        } else if name.ends_with(".zip") {
+            Self::Zip
+        } else if name.ends_with(".7z") {
+            Self::SevenZip
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn is_native(&self) -> bool {
+        *self != Self::Other
+    }
+}
+
+#[async_trait::async_trait]
+pub trait ArchiveBackend: Send + Sync {
+    async fn list(&self, archive: &Path, password: Option<&str>) -> Result<Vec<ArchiveEntry>>;
+
+    // Extracts the entries selected by `filter`, rebased onto its subtree root
+    async fn extract(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        password: Option<&str>,
+        filter: &EntryFilter,
+        progress: Option<ProgressReporter>,
+    ) -> Result<()>;
+
+    // Extracts a single entry flat into `destination` and returns the written file
+    async fn extract_one(
+        &self,
+        archive: &Path,
+        entry: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<PathBuf>;
+
+    // Extracts the listed entries in a single pass, each at its sanitized path below `destination`.
+    // Entries missing from the archive are skipped, callers check what was written
+    async fn extract_many(
+        &self,
+        archive: &Path,
+        entries: &[String],
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<()>;
+
+    async fn test(&self, archive: &Path, password: Option<&str>) -> Result<ArchiveTestReport>;
+}
+
+pub fn select_backend(archive: &Path) -> Box<dyn ArchiveBackend> {
+    // Unknown extensions fall back to the file's leading bytes
+    let format = match ArchiveFormat::from_path(archive) {
+        ArchiveFormat::Other => sniff_file(archive)
+            .ok()
+            .flatten()
+            .map(|format| format.archive_format())
+            .unwrap_or(ArchiveFormat::Other),
+        format => format,
+    };
+    #[cfg(windows)]
+    if !format.is_native() || VolumeSet::is_volume_path(archive) {
+        return Box::new(SevenZipBackend);
+    }
+    // Elsewhere unsupported formats fail when the native backend opens them
+    Box::new(NativeBackend::new(format))
+}
@@ -0,0 +1,272 @@
+use super::{
+    ExtractionFilter, ProgressReporter, UnwrappedArchive, VolumeSet, extract_archive_files,
+    join_entry_path, sanitize_path, select_backend,
+};
+use crate::configs::{ConfigFile, settings::Settings};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+const CACHE_DIR: &str = "Cache";
+const FILES_DIR: &str = "files";
+const RECORD_FILE: &str = "record.json";
+
+lazy_static::lazy_static! {
+    static ref LEASES: Mutex<HashMap<String, Arc<AsyncMutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+// Stored next to the extracted files of a slot
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
+struct CacheRecord {
+    // Every entry of the archive has been extracted
+    complete: bool,
+    // Lowercase sanitized paths of entries extracted on their own
+    entries: Vec<String>,
+    size: u64,
+    last_used: i64,
+}
+
+// Extractions shared by details, suggestions and installer runs, one slot per archive and password.
+// Installs extract through their filter straight into staging and never read from here.
+pub struct ExtractionCache {
+    root: PathBuf,
+}
+
+// A slot in use, eviction leaves it alone until this is dropped
+pub struct CachedExtraction {
+    pub dir: PathBuf,
+    _lease: OwnedMutexGuard<()>,
+}
+
+// Scratch space of the app, everything but the cache is dropped on exit
+pub fn temp_root() -> PathBuf {
+    std::env::temp_dir().join("AppPorter")
+}
+
+impl Default for ExtractionCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExtractionCache {
+    pub fn new() -> Self {
+        Self {
+            root: temp_root().join(CACHE_DIR),
+        }
+    }
+
+    // Makes sure the listed entries of the payload are in the cache, missing ones are extracted in one pass
+    pub async fn extract_entries(
+        &self,
+        zip_path: &str,
+        payload: &UnwrappedArchive,
+        entries: &[String],
+        password: Option<&str>,
+    ) -> Result<CachedExtraction> {
+        let (slot, lease, mut record) = self.open_slot(zip_path, password).await?;
+        let dir = slot.join(FILES_DIR);
+
+        let missing: Vec<String> = entries
+            .iter()
+            .filter(|entry| {
+                !record.complete
+                    && !record
+                        .entries
+                        .contains(&sanitize_path(entry).to_lowercase())
+            })
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            select_backend(&payload.path)
+                .extract_many(&payload.path, &missing, &dir, password)
+                .await?;
+            for entry in missing {
+                let path = sanitize_path(&entry);
+                if join_entry_path(&dir, &path).exists() {
+                    record.entries.push(path.to_lowercase());
+                }
+            }
+        }
+
+        self.commit(&slot, record).await?;
+        Ok(CachedExtraction { dir, _lease: lease })
+    }
+
+    // Extracts the whole archive under the safety policy unless an earlier call already did
+    pub async fn extract_archive(
+        &self,
+        zip_path: &str,
+        password: &str,
+        progress: Option<ProgressReporter>,
+    ) -> Result<CachedExtraction> {
+        let (slot, lease, mut record) = self.open_slot(zip_path, Some(password)).await?;
+        let dir = slot.join(FILES_DIR);
+
+        if !record.complete {
+            extract_archive_files(
+                zip_path,
+                &dir.to_string_lossy(),
+                &ExtractionFilter::default(),
+                progress,
+                password,
+            )
+            .await?;
+            record.complete = true;
+            record.entries.clear();
+        }
+
+        self.commit(&slot, record).await?;
+        Ok(CachedExtraction { dir, _lease: lease })
+    }
+
+    // Drops the least recently used slots until the cache fits in `limit` bytes
+    pub async fn evict(&self, limit: u64) -> Result<()> {
+        let Ok(mut dir) = tokio::fs::read_dir(&self.root).await else {
+            return Ok(());
+        };
+        let mut slots = Vec::new();
+        while let Some(entry) = dir.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                let record = read_record(&entry.path()).await;
+                slots.push((entry.file_name().to_string_lossy().to_string(), record));
+            }
+        }
+        slots.sort_by_key(|(_, record)| record.last_used);
+
+        let mut total: u64 = slots.iter().map(|(_, record)| record.size).sum();
+        for (key, record) in slots {
+            if total <= limit {
+                break;
+            }
+            // Slots held by a running operation are skipped
+            let Ok(_lease) = lease(&key)?.try_lock_owned() else {
+                continue;
+            };
+            tokio::fs::remove_dir_all(self.root.join(&key)).await?;
+            total = total.saturating_sub(record.size);
+        }
+        Ok(())
+    }
+
+    // Called on exit: removes the other scratch folders and trims the cache to its size limit
+    pub async fn cleanup(&self) -> Result<()> {
+        let root = temp_root();
+        if let Ok(mut dir) = tokio::fs::read_dir(&root).await {
+            while let Some(entry) = dir.next_entry().await? {
+                if entry.path() == self.root {
+                    continue;
+                }
+                if entry.file_type().await?.is_dir() {
+                    tokio::fs::remove_dir_all(entry.path()).await?;
+                } else {
+                    tokio::fs::remove_file(entry.path()).await?;
+                }
+            }
+        }
+        self.evict(size_limit().await?).await
+    }
+
+    async fn open_slot(
+        &self,
+        zip_path: &str,
+        password: Option<&str>,
+    ) -> Result<(PathBuf, OwnedMutexGuard<()>, CacheRecord)> {
+        let key = format!(
+            "{}-{}",
+            archive_key(Path::new(zip_path)).await?,
+            password_fingerprint(password.unwrap_or_default())
+        );
+        let lease = lease(&key)?.lock_owned().await;
+        let slot = self.root.join(&key);
+        tokio::fs::create_dir_all(slot.join(FILES_DIR)).await?;
+        let record = read_record(&slot).await;
+        Ok((slot, lease, record))
+    }
+
+    async fn commit(&self, slot: &Path, mut record: CacheRecord) -> Result<()> {
+        let files = slot.join(FILES_DIR);
+        record.size = tokio::task::spawn_blocking(move || dir_size(&files)).await??;
+        record.last_used = chrono::Utc::now().timestamp_millis();
+        tokio::fs::write(slot.join(RECORD_FILE), serde_json::to_string(&record)?).await?;
+        self.evict(size_limit().await?).await
+    }
+}
+
+async fn size_limit() -> Result<u64> {
+    Ok(Settings::read().await?.archive.cache_size_limit_mb * 1024 * 1024)
+}
+
+fn lease(key: &str) -> Result<Arc<AsyncMutex<()>>> {
+    Ok(LEASES
+        .lock()
+        .map_err(|_| anyhow!("Failed to lock extraction cache"))?
+        .entry(key.to_owned())
+        .or_default()
+        .clone())
+}
+
+// A missing or unreadable record makes the slot look empty and old
+async fn read_record(slot: &Path) -> CacheRecord {
+    tokio::fs::read_to_string(slot.join(RECORD_FILE))
+        .await
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Identifies an archive by its location, size and modification time, so an edited archive gets a fresh slot
+async fn archive_key(path: &Path) -> Result<String> {
+    let volumes = match VolumeSet::detect(path)? {
+        Some(set) => set.volumes,
+        None => vec![path.to_owned()],
+    };
+    let mut size = 0;
+    let mut modified = SystemTime::UNIX_EPOCH;
+    for volume in &volumes {
+        let metadata = tokio::fs::metadata(volume).await?;
+        size += metadata.len();
+        modified = modified.max(metadata.modified()?);
+    }
+    let modified = modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+
+    let path = std::fs::canonicalize(path)?;
+    let digest = Sha256::digest(format!(
+        "{}\0{size}\0{modified}",
+        path.to_string_lossy().to_lowercase()
+    ));
+    Ok(to_hex(&digest[..16]))
+}
+
+// Tells slots of different passwords apart without storing the password itself
+pub fn password_fingerprint(password: &str) -> String {
+    let digest = Sha256::digest(format!("AppPorter extraction cache\0{password}"));
+    to_hex(&digest[..8])
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn dir_size(path: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else if file_type.is_file() {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub is_dir: bool,
+    #[ts(type = "number")]
+    pub size: u64,
+    #[ts(type = "number")]
+    pub packed_size: u64,
+    pub modified: String,
+    pub crc: Option<u32>,
+    pub attributes: String,
+    pub encrypted: bool,
+    // Target of a symbolic or hard link entry, as stored in the archive
+    pub link_target: Option<String>,
+}
+
+pub const MODIFIED_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+impl ArchiveEntry {
+    pub fn name(&self) -> &str {
+        self.path.rsplit('\\').next().unwrap_or(&self.path)
+    }
+}
+
+// Renders Windows file attribute bits the way 7-Zip prints them in its technical listing
+pub fn format_attributes(attributes: u32) -> String {
+    [
+        (0x10, 'D'),
+        (0x01, 'R'),
+        (0x02, 'H'),
+        (0x04, 'S'),
+        (0x20, 'A'),
+    ]
+    .iter()
+    .filter(|(bit, _)| attributes & bit != 0)
+    .map(|(_, flag)| *flag)
+    .collect()
+}
+
+// Leading separators are kept so that absolute entries remain visible to the extraction policy
+pub fn normalize_entry_path(path: &str) -> String {
+    path.replace('/', "\\").trim_end_matches('\\').to_owned()
+}
@@ -0,0 +1,110 @@
+use super::sanitize_path;
+use anyhow::{Result, anyhow};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Limits an extraction to a subtree of the archive and to entries matching the glob lists.
+// Globs are matched against paths relative to `root`, e.g. `bin/**` or `*.pdb`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct ExtractionFilter {
+    pub root: String,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct EntryFilter {
+    root: String,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl ExtractionFilter {
+    pub fn compile(&self) -> Result<EntryFilter> {
+        Ok(EntryFilter {
+            root: sanitize_path(&self.root),
+            include: build_glob_set(&self.include)?,
+            exclude: build_glob_set(&self.exclude)?,
+        })
+    }
+
+    // Rebases an archive path onto the subtree root, ignoring the glob lists
+    pub fn relative_path(&self, path: &str) -> Option<String> {
+        strip_root(&sanitize_path(path), &sanitize_path(&self.root)).map(str::to_owned)
+    }
+}
+
+impl EntryFilter {
+    pub fn is_passthrough(&self) -> bool {
+        self.root.is_empty() && self.include.is_none() && self.exclude.is_none()
+    }
+
+    // Returns where an entry lands below the destination, or `None` if it is filtered out
+    pub fn target(&self, entry_path: &str) -> Option<String> {
+        let path = sanitize_path(entry_path);
+        let relative = strip_root(&path, &self.root)?;
+        if relative.is_empty() {
+            return None;
+        }
+
+        if let Some(exclude) = &self.exclude
+            && matches_path(exclude, relative)
+        {
+            return None;
+        }
+        if let Some(include) = &self.include
+            && !matches_path(include, relative)
+        {
+            return None;
+        }
+        Some(relative.to_owned())
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    let patterns: Vec<&str> = patterns
+        .iter()
+        .map(|pattern| pattern.trim().trim_matches(['/', '\\']))
+        .filter(|pattern| !pattern.is_empty())
+        .collect();
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(&pattern.replace('\\', "/"))
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| anyhow!("Invalid glob pattern '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    Ok(Some(builder.build()?))
+}
+
+fn strip_root<'a>(path: &'a str, root: &str) -> Option<&'a str> {
+    if root.is_empty() {
+        return Some(path);
+    }
+    let prefix = path.get(..root.len())?;
+    if !prefix.eq_ignore_ascii_case(root) {
+        return None;
+    }
+    match path[root.len()..].strip_prefix('\\') {
+        Some(rest) => Some(rest),
+        None if path.len() == root.len() => Some(""),
+        None => None,
+    }
+}
+
+// A pattern naming a directory also selects everything below it
+fn matches_path(set: &GlobSet, relative: &str) -> bool {
+    let path = relative.replace('\\', "/");
+    path.match_indices('/')
+        .map(|(i, _)| &path[..i])
+        .chain(std::iter::once(path.as_str()))
+        .any(|candidate| set.is_match(candidate))
+}
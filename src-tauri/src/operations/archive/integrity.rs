@@ -0,0 +1,58 @@
+use super::{open_payload, select_backend};
+use crate::configs::{ConfigFile, settings::Settings};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct EntryTestFailure {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ArchiveTestReport {
+    #[ts(type = "number")]
+    pub tested: u64,
+    pub failures: Vec<EntryTestFailure>,
+}
+
+impl ArchiveTestReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+// Decompresses every entry without writing anything and reports the ones failing their checks
+pub async fn test_archive(path: &str, password: Option<&str>) -> Result<ArchiveTestReport> {
+    let payload = open_payload(path, password).await?;
+    select_backend(&payload.path)
+        .test(&payload.path, password)
+        .await
+}
+
+// Runs `test_archive` before an install when enabled in settings
+pub async fn ensure_archive_intact(path: &str, password: &str) -> Result<()> {
+    if !Settings::read().await?.archive.test_before_install {
+        return Ok(());
+    }
+
+    let report = test_archive(path, Some(password)).await?;
+    if report.is_ok() {
+        return Ok(());
+    }
+
+    let mut summary = report
+        .failures
+        .iter()
+        .take(3)
+        .map(|failure| format!("{} ({})", failure.path, failure.reason))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if report.failures.len() > 3 {
+        summary.push_str(&format!(" and {} more", report.failures.len() - 3));
+    }
+    Err(anyhow!("Archive integrity check failed: {}", summary))
+}
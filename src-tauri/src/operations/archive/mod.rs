@@ -0,0 +1,116 @@
+pub mod backend;
+pub mod cache;
+pub mod entry;
+pub mod filter;
+pub mod integrity;
+pub mod native;
+pub mod nested;
+pub mod password;
+pub mod policy;
+pub mod progress;
+pub mod search;
+// Drives the bundled 7z.exe, which only exists on Windows
+#[cfg(windows)]
+pub mod seven_zip;
+pub mod sniff;
+pub mod volume;
+
+pub use backend::*;
+pub use cache::*;
+pub use entry::*;
+pub use filter::*;
+pub use integrity::*;
+pub use native::*;
+pub use nested::*;
+pub use password::*;
+pub use policy::*;
+pub use progress::*;
+pub use search::*;
+#[cfg(windows)]
+pub use seven_zip::*;
+pub use sniff::*;
+pub use volume::*;
+
+use crate::configs::{ConfigFile, settings::Settings};
+use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+
+pub fn sanitize_path(path: &str) -> String {
+    path.replace('/', "\\")
+        .split('\\')
+        .filter(|part| !part.is_empty() && *part != "." && *part != ".." && !part.contains(':'))
+        .collect::<Vec<_>>()
+        .join("\\")
+}
+
+// Joins a backslash separated entry path below `root` one component at a time, so it nests on every platform
+pub fn join_entry_path(root: &Path, relative: &str) -> PathBuf {
+    relative
+        .split('\\')
+        .filter(|part| !part.is_empty())
+        .fold(root.to_owned(), |path, part| path.join(part))
+}
+
+pub async fn get_archive_content(path: &str, password: Option<&str>) -> Result<Vec<ArchiveEntry>> {
+    let password = match password {
+        Some(password) => password.to_owned(),
+        None => saved_password(path).await?,
+    };
+    Ok(open_payload(path, Some(&password)).await?.entries)
+}
+
+// Falls back to the vault when no password was given, "Wrong password" asks the user for one
+pub async fn saved_password(path: &str) -> Result<String> {
+    resolve_archive_password(path, None, path)
+        .await?
+        .password
+        .ok_or(anyhow!("Wrong password"))
+}
+
+pub async fn extract_archive_files(
+    zip_path: &str,
+    install_path: &str,
+    filter: &ExtractionFilter,
+    progress: Option<ProgressReporter>,
+    password: &str,
+) -> Result<()> {
+    let filter = filter.compile()?;
+    // Surface missing volumes as they are instead of as a generic listing failure
+    resolve_volume_path(Path::new(zip_path))?;
+    let payload = open_payload(zip_path, Some(password)).await.map_err(|e| {
+        if e.to_string() == "Wrong password" {
+            e
+        } else {
+            anyhow!("Failed to list archive contents")
+        }
+    })?;
+    let selected: Vec<ArchiveEntry> = payload
+        .entries
+        .iter()
+        .filter(|entry| filter.target(&entry.path).is_some())
+        .cloned()
+        .collect();
+    if selected.is_empty() {
+        return Err(anyhow!("No archive entries match the extraction filter"));
+    }
+
+    let policy = Settings::read().await?.archive.extraction_policy;
+    let report = policy.check(&selected, Path::new(install_path), &filter);
+    if report.is_blocking() {
+        return Err(anyhow!(
+            "Extraction blocked by safety policy: {}",
+            report.summary()
+        ));
+    }
+
+    // Straight into the destination through the filter, so unselected entries never touch the disk
+    select_backend(&payload.path)
+        .extract(
+            &payload.path,
+            Path::new(install_path),
+            Some(password),
+            &filter,
+            progress,
+        )
+        .await
+}
@@ -0,0 +1,659 @@
+use super::{
+    ArchiveBackend, ArchiveEntry, ArchiveFormat, ArchiveTestReport, EntryFilter, EntryTestFailure,
+    MODIFIED_FORMAT, ProgressReporter, format_attributes, join_entry_path, normalize_entry_path,
+    sanitize_path,
+};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDateTime};
+use sevenz_rust2::{Archive, ArchiveReader, EncoderMethod, Password};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use zip::ZipArchive;
+use zip::read::ZipFile;
+use zip::result::ZipError;
+
+// Pure Rust backend for zip, 7z and the tar family
+pub struct NativeBackend {
+    format: ArchiveFormat,
+}
+
+struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+impl NativeBackend {
+    pub fn new(format: ArchiveFormat) -> Self {
+        Self { format }
+    }
+}
+
+#[async_trait::async_trait]
+impl ArchiveBackend for NativeBackend {
+    async fn list(&self, archive: &Path, password: Option<&str>) -> Result<Vec<ArchiveEntry>> {
+        let format = self.format;
+        let archive = archive.to_owned();
+        let password = password.unwrap_or_default().to_owned();
+
+        tokio::task::spawn_blocking(move || list_entries(format, &archive, &password)).await?
+    }
+
+    async fn extract(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        password: Option<&str>,
+        filter: &EntryFilter,
+        progress: Option<ProgressReporter>,
+    ) -> Result<()> {
+        let format = self.format;
+        let archive = archive.to_owned();
+        let destination = destination.to_owned();
+        let password = password.unwrap_or_default().to_owned();
+        let filter = filter.clone();
+
+        tokio::task::spawn_blocking(move || {
+            extract_entries(format, &archive, &destination, &password, &filter, progress)
+        })
+        .await?
+    }
+
+    async fn extract_one(
+        &self,
+        archive: &Path,
+        entry: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<PathBuf> {
+        let format = self.format;
+        let archive = archive.to_owned();
+        let wanted = sanitize_path(entry);
+        let destination = destination.to_owned();
+        let password = password.unwrap_or_default().to_owned();
+
+        tokio::task::spawn_blocking(move || {
+            let file_name = Path::new(&wanted)
+                .file_name()
+                .ok_or(anyhow!("Failed to get file name"))?
+                .to_owned();
+            let target = destination.join(file_name);
+            let mut found = false;
+
+            for_each_entry(format, &archive, &password, None, |entry, reader| {
+                if !entry.is_dir
+                    && entry.link_target.is_none()
+                    && sanitize_path(&entry.path).eq_ignore_ascii_case(&wanted)
+                {
+                    io::copy(reader, &mut File::create(&target)?)?;
+                    found = true;
+                    return Ok(false);
+                }
+                Ok(true)
+            })?;
+
+            if !found {
+                return Err(anyhow!("Failed to find '{}' in archive", wanted));
+            }
+            Ok(target)
+        })
+        .await?
+    }
+
+    async fn extract_many(
+        &self,
+        archive: &Path,
+        entries: &[String],
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let format = self.format;
+        let archive = archive.to_owned();
+        let destination = destination.to_owned();
+        let password = password.unwrap_or_default().to_owned();
+        let mut wanted: HashMap<String, String> = entries
+            .iter()
+            .map(|entry| {
+                let path = sanitize_path(entry);
+                (path.to_lowercase(), path)
+            })
+            .collect();
+
+        tokio::task::spawn_blocking(move || {
+            for_each_entry(format, &archive, &password, None, |entry, reader| {
+                if entry.is_dir || entry.link_target.is_some() {
+                    return Ok(true);
+                }
+                let Some(path) = wanted.remove(&sanitize_path(&entry.path).to_lowercase()) else {
+                    return Ok(true);
+                };
+                let target = join_entry_path(&destination, &path);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                io::copy(reader, &mut File::create(&target)?)?;
+                Ok(!wanted.is_empty())
+            })
+        })
+        .await?
+    }
+
+    async fn test(&self, archive: &Path, password: Option<&str>) -> Result<ArchiveTestReport> {
+        let format = self.format;
+        let archive = archive.to_owned();
+        let password = password.unwrap_or_default().to_owned();
+
+        tokio::task::spawn_blocking(move || {
+            let mut report = ArchiveTestReport::default();
+            let result = for_each_entry(format, &archive, &password, None, |entry, reader| {
+                if entry.is_dir || entry.link_target.is_some() {
+                    return Ok(true);
+                }
+                report.tested += 1;
+                // Readers verify the stored CRC once the entry has been read to the end
+                if let Err(e) = io::copy(reader, &mut io::sink()) {
+                    let e = anyhow::Error::from(e);
+                    if entry.encrypted && is_checksum_error(&e) {
+                        return Err(anyhow!("Wrong password"));
+                    }
+                    report.failures.push(EntryTestFailure {
+                        path: entry.path.clone(),
+                        reason: e.to_string(),
+                    });
+                }
+                Ok(true)
+            });
+
+            match result {
+                Ok(()) => Ok(report),
+                Err(e) if e.to_string() == "Wrong password" => Err(e),
+                Err(e) => {
+                    report.failures.push(EntryTestFailure {
+                        path: archive
+                            .file_name()
+                            .map(|name| name.to_string_lossy().to_string())
+                            .unwrap_or_default(),
+                        reason: e.to_string(),
+                    });
+                    Ok(report)
+                }
+            }
+        })
+        .await?
+    }
+}
+
+fn list_entries(
+    format: ArchiveFormat,
+    archive: &Path,
+    password: &str,
+) -> Result<Vec<ArchiveEntry>> {
+    let mut entries = match format {
+        ArchiveFormat::Zip => {
+            let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+            let mut entries = Vec::with_capacity(zip.len());
+            for index in 0..zip.len() {
+                let (mut entry, is_link) = {
+                    let file = zip.by_index_raw(index)?;
+                    (zip_entry(&file)?, file.is_symlink() && !file.encrypted())
+                };
+                // Symlinks store their target as the entry contents
+                if is_link {
+                    let mut target = String::new();
+                    zip.by_index(index)?.read_to_string(&mut target)?;
+                    entry.link_target = Some(target);
+                }
+                entries.push(entry);
+            }
+            entries
+        }
+        ArchiveFormat::SevenZip => sevenz_entries(
+            &Archive::open_with_password(archive, &Password::from(password))
+                .map_err(map_7z_error)?,
+        ),
+        _ => {
+            let mut entries = Vec::new();
+            for_each_entry(format, archive, password, None, |entry, _| {
+                entries.push(entry.clone());
+                Ok(true)
+            })?;
+            entries
+        }
+    };
+
+    entries.retain(|entry| !entry.path.is_empty());
+    Ok(entries)
+}
+
+fn extract_entries(
+    format: ArchiveFormat,
+    archive: &Path,
+    destination: &Path,
+    password: &str,
+    filter: &EntryFilter,
+    progress: Option<ProgressReporter>,
+) -> Result<()> {
+    // Tar streams have no index, so their totals are estimated from the compressed input
+    let consumed = Arc::new(AtomicU64::new(0));
+    let input_len = fs::metadata(archive)?.len();
+    let indexed = matches!(format, ArchiveFormat::Zip | ArchiveFormat::SevenZip);
+    if indexed && let Some(progress) = &progress {
+        let mut entries = list_entries(format, archive, password)?;
+        entries.retain(|entry| filter.target(&entry.path).is_some());
+        progress.set_totals(
+            entries.iter().map(|entry| entry.size).sum(),
+            entries.iter().filter(|entry| !entry.is_dir).count() as u64,
+        );
+    }
+
+    let mut written = 0u64;
+    let mut files_done = 0u64;
+
+    for_each_entry(
+        format,
+        archive,
+        password,
+        Some(consumed.clone()),
+        |entry, reader| {
+            // Links are never materialized by the native backend
+            if entry.link_target.is_some() {
+                return Ok(true);
+            }
+            let Some(relative) = filter.target(&entry.path) else {
+                return Ok(true);
+            };
+            let target = join_entry_path(destination, &relative);
+
+            if entry.is_dir {
+                fs::create_dir_all(&target)?;
+                return Ok(true);
+            }
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(&target)?;
+            copy_with(reader, &mut file, |n| {
+                written += n;
+                if let Some(progress) = &progress {
+                    if !indexed {
+                        let read = consumed.load(Ordering::Relaxed).max(1);
+                        progress.set_totals(written * input_len / read, 0);
+                    }
+                    progress.update(written, files_done, &entry.path);
+                }
+            })?;
+            files_done += 1;
+            Ok(true)
+        },
+    )?;
+
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+    Ok(())
+}
+
+fn copy_with(
+    reader: &mut dyn Read,
+    writer: &mut impl Write,
+    mut on_chunk: impl FnMut(u64),
+) -> io::Result<()> {
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(());
+        }
+        writer.write_all(&buffer[..n])?;
+        on_chunk(n as u64);
+    }
+}
+
+// Walks every entry in archive order, handing the visitor a reader over its contents.
+// The visitor returns `false` to stop early.
+fn for_each_entry(
+    format: ArchiveFormat,
+    archive: &Path,
+    password: &str,
+    consumed: Option<Arc<AtomicU64>>,
+    mut visitor: impl FnMut(&ArchiveEntry, &mut dyn Read) -> Result<bool>,
+) -> Result<()> {
+    let counted = || -> Result<CountingReader<BufReader<File>>> {
+        Ok(CountingReader {
+            inner: BufReader::new(File::open(archive)?),
+            count: consumed.clone().unwrap_or_default(),
+        })
+    };
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+            for index in 0..zip.len() {
+                let encrypted = zip.by_index_raw(index)?.encrypted();
+                let mut entry = if encrypted {
+                    if password.is_empty() {
+                        return Err(anyhow!("Wrong password"));
+                    }
+                    zip.by_index_decrypt(index, password.as_bytes())
+                } else {
+                    zip.by_index(index)
+                }
+                .map_err(map_zip_error)?;
+
+                if entry.is_symlink() {
+                    continue;
+                }
+                let metadata = zip_entry(&entry)?;
+                let proceed = visitor(&metadata, &mut entry).map_err(|e| {
+                    if encrypted && is_checksum_error(&e) {
+                        anyhow!("Wrong password")
+                    } else {
+                        e
+                    }
+                })?;
+                if !proceed {
+                    break;
+                }
+            }
+        }
+        ArchiveFormat::SevenZip => {
+            let mut reader =
+                ArchiveReader::open(archive, Password::from(password)).map_err(map_7z_error)?;
+            let entries = sevenz_entries(reader.archive());
+            let index: HashMap<String, usize> = reader
+                .archive()
+                .files
+                .iter()
+                .enumerate()
+                .map(|(i, file)| (file.name().to_owned(), i))
+                .collect();
+
+            // Returning false only ends the current block, so later blocks are skipped here
+            let mut stopped = false;
+            let mut failure = None;
+            reader
+                .for_each_entries(|entry, data| {
+                    if stopped {
+                        return Ok(false);
+                    }
+                    let Some(metadata) = index.get(entry.name()).map(|&i| &entries[i]) else {
+                        return Ok(true);
+                    };
+                    match visitor(metadata, data) {
+                        Ok(proceed) => {
+                            stopped = !proceed;
+                            Ok(proceed)
+                        }
+                        Err(e) => {
+                            failure = Some(e);
+                            stopped = true;
+                            Ok(false)
+                        }
+                    }
+                })
+                .map_err(map_7z_error)?;
+            if let Some(e) = failure {
+                return Err(e);
+            }
+        }
+        ArchiveFormat::Tar => visit_tar(counted()?, visitor)?,
+        ArchiveFormat::TarGz => visit_tar(flate2::read::MultiGzDecoder::new(counted()?), visitor)?,
+        ArchiveFormat::TarXz => visit_tar(lzma_rust2::XzReader::new(counted()?, true), visitor)?,
+        ArchiveFormat::TarBz2 => visit_tar(bzip2::read::MultiBzDecoder::new(counted()?), visitor)?,
+        ArchiveFormat::Other => return Err(anyhow!("Unsupported archive format")),
+    }
+
+    Ok(())
+}
+
+fn visit_tar<R: Read>(
+    reader: R,
+    mut visitor: impl FnMut(&ArchiveEntry, &mut dyn Read) -> Result<bool>,
+) -> Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let header = entry.header();
+        let entry_type = header.entry_type();
+        let is_link = entry_type.is_symlink() || entry_type.is_hard_link();
+        if !entry_type.is_dir() && !entry_type.is_file() && !is_link {
+            continue;
+        }
+        let link_target = if is_link {
+            header
+                .link_name()?
+                .map(|target| target.to_string_lossy().to_string())
+        } else {
+            None
+        };
+
+        let size = entry.size();
+        let metadata = ArchiveEntry {
+            path: normalize_entry_path(&entry.path()?.to_string_lossy()),
+            is_dir: entry_type.is_dir(),
+            size,
+            packed_size: size,
+            modified: header
+                .mtime()
+                .ok()
+                .and_then(|mtime| DateTime::from_timestamp(mtime as i64, 0))
+                .map(|time| time.format(MODIFIED_FORMAT).to_string())
+                .unwrap_or_default(),
+            crc: None,
+            attributes: if entry_type.is_dir() { "D" } else { "A" }.to_owned(),
+            encrypted: false,
+            link_target,
+        };
+        if !visitor(&metadata, &mut entry)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn zip_entry<R: Read>(file: &ZipFile<'_, R>) -> Result<ArchiveEntry> {
+    let is_dir = file.is_dir();
+    let attributes = match file.unix_mode() {
+        Some(mode) if is_dir || mode & 0o200 == 0 => {
+            format_attributes(if is_dir { 0x10 } else { 0x01 })
+        }
+        Some(_) => "A".to_owned(),
+        None => format_attributes(file.external_attributes() & 0xFF),
+    };
+
+    Ok(ArchiveEntry {
+        path: normalize_entry_path(&file.name()?),
+        is_dir,
+        size: file.size(),
+        packed_size: file.compressed_size(),
+        modified: file
+            .last_modified()
+            .and_then(|time| NaiveDateTime::try_from(time).ok())
+            .map(|time| time.format(MODIFIED_FORMAT).to_string())
+            .unwrap_or_default(),
+        crc: (!is_dir).then(|| file.crc32()),
+        attributes,
+        encrypted: file.encrypted(),
+        link_target: None,
+    })
+}
+
+fn sevenz_entries(archive: &Archive) -> Vec<ArchiveEntry> {
+    let encrypted_blocks: Vec<bool> = archive
+        .blocks
+        .iter()
+        .map(|block| {
+            block
+                .coders
+                .iter()
+                .any(|coder| coder.encoder_method_id() == EncoderMethod::ID_AES256_SHA256)
+        })
+        .collect();
+
+    archive
+        .files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| {
+            let block = archive.stream_map.file_block_index[index];
+            let attributes = if file.has_windows_attributes {
+                file.windows_attributes()
+            } else if file.is_directory() {
+                0x10
+            } else {
+                0
+            };
+
+            ArchiveEntry {
+                path: normalize_entry_path(file.name()),
+                is_dir: file.is_directory(),
+                size: file.size(),
+                packed_size: file.compressed_size,
+                modified: if file.has_last_modified_date {
+                    filetime_to_string(u64::from(file.last_modified_date()))
+                } else {
+                    String::new()
+                },
+                crc: file.has_crc.then_some(file.crc as u32),
+                attributes: format_attributes(attributes),
+                encrypted: block.is_some_and(|block| encrypted_blocks[block]),
+                link_target: None,
+            }
+        })
+        .collect()
+}
+
+// Converts a Windows FILETIME (100ns ticks since 1601) to the listing timestamp format
+fn filetime_to_string(filetime: u64) -> String {
+    let seconds = (filetime / 10_000_000) as i64 - 11_644_473_600;
+    DateTime::from_timestamp(seconds, 0)
+        .map(|time| time.format(MODIFIED_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+fn map_zip_error(error: ZipError) -> anyhow::Error {
+    match error {
+        ZipError::InvalidPassword | ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => {
+            anyhow!("Wrong password")
+        }
+        e => e.into(),
+    }
+}
+
+fn map_7z_error(error: sevenz_rust2::Error) -> anyhow::Error {
+    match error {
+        sevenz_rust2::Error::PasswordRequired | sevenz_rust2::Error::MaybeBadPassword(_) => {
+            anyhow!("Wrong password")
+        }
+        e => e.into(),
+    }
+}
+
+// A bad ZipCrypto password only surfaces as a checksum error while reading
+fn is_checksum_error(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|e| e.to_string().contains("Invalid checksum"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::ExtractionFilter;
+
+    // The same tree in every format: app\bin\tool.exe, app\bin\tool.pdb and app\readme.txt
+    const FIXTURES: [(&str, ArchiveFormat); 3] = [
+        ("sample.zip", ArchiveFormat::Zip),
+        ("sample.7z", ArchiveFormat::SevenZip),
+        ("sample.tar.gz", ArchiveFormat::TarGz),
+    ];
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/archive")
+            .join(name)
+    }
+
+    #[tokio::test]
+    async fn lists_every_format() {
+        for (name, format) in FIXTURES {
+            let entries = NativeBackend::new(format)
+                .list(&fixture(name), None)
+                .await
+                .unwrap();
+            let mut files: Vec<(&str, u64)> = entries
+                .iter()
+                .filter(|entry| !entry.is_dir)
+                .map(|entry| (entry.path.as_str(), entry.size))
+                .collect();
+            files.sort();
+            assert_eq!(
+                files,
+                [
+                    ("app\\bin\\tool.exe", 16),
+                    ("app\\bin\\tool.pdb", 14),
+                    ("app\\readme.txt", 23),
+                ],
+                "{name}"
+            );
+            assert!(
+                entries
+                    .iter()
+                    .any(|entry| entry.is_dir && entry.path == "app\\bin"),
+                "{name}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn extracts_through_the_filter() {
+        let filter = ExtractionFilter {
+            root: "app".to_owned(),
+            include: Vec::new(),
+            exclude: vec!["*.pdb".to_owned()],
+        }
+        .compile()
+        .unwrap();
+
+        for (name, format) in FIXTURES {
+            let destination = tempfile::tempdir().unwrap();
+            NativeBackend::new(format)
+                .extract(&fixture(name), destination.path(), None, &filter, None)
+                .await
+                .unwrap();
+
+            let root = destination.path();
+            assert_eq!(
+                fs::read(root.join("bin").join("tool.exe")).unwrap(),
+                b"MZ fixture tool\n",
+                "{name}"
+            );
+            assert!(root.join("readme.txt").is_file(), "{name}");
+            assert!(!root.join("bin").join("tool.pdb").exists(), "{name}");
+            assert!(!root.join("app").exists(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_unsupported_formats() {
+        let result = NativeBackend::new(ArchiveFormat::Other)
+            .list(&fixture("sample.zip"), None)
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use super::{ArchiveEntry, ArchiveFormat, resolve_volume_path, select_backend};
+use crate::SUPPORTED_EXTENSIONS;
+use crate::configs::{ConfigFile, settings::Settings};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+// The innermost archive reached by unwrapping, plus the entries that led to it.
+// Unwrapped layers live in a temp directory that is removed when this is dropped.
+pub struct UnwrappedArchive {
+    pub path: PathBuf,
+    pub layers: Vec<String>,
+    pub entries: Vec<ArchiveEntry>,
+    _temp_dir: Option<TempDir>,
+}
+
+impl UnwrappedArchive {
+    pub fn depth(&self) -> u32 {
+        self.layers.len() as u32
+    }
+}
+
+pub fn is_archive_name(name: &str) -> bool {
+    let path = Path::new(name);
+    ArchiveFormat::from_path(path).is_native()
+        || path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .is_some_and(|ext| SUPPORTED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+// Opens an archive using the unwrap depth configured in settings
+pub async fn open_payload(path: &str, password: Option<&str>) -> Result<UnwrappedArchive> {
+    let max_depth = Settings::read().await?.archive.unwrap_depth;
+    let path = resolve_volume_path(Path::new(path))?;
+    unwrap_nested_archive(&path, password, max_depth).await
+}
+
+// Follows archives whose only file is another archive, e.g. a `.zip` wrapping a `.zip`
+pub async fn unwrap_nested_archive(
+    path: &Path,
+    password: Option<&str>,
+    max_depth: u32,
+) -> Result<UnwrappedArchive> {
+    let mut current = path.to_owned();
+    let mut entries = select_backend(&current).list(&current, password).await?;
+    let mut layers = Vec::new();
+    let mut temp_dir: Option<TempDir> = None;
+
+    while (layers.len() as u32) < max_depth {
+        let mut files = entries.iter().filter(|entry| !entry.is_dir);
+        let (Some(inner), None) = (files.next(), files.next()) else {
+            break;
+        };
+        if !is_archive_name(inner.name()) {
+            break;
+        }
+
+        let work_dir = match temp_dir {
+            Some(ref dir) => dir.path().to_owned(),
+            None => {
+                let root = std::env::temp_dir().join("AppPorter");
+                tokio::fs::create_dir_all(&root).await?;
+                let dir = tempfile::Builder::new()
+                    .prefix("unwrap_")
+                    .tempdir_in(root)?;
+                let work_dir = dir.path().to_owned();
+                temp_dir = Some(dir);
+                work_dir
+            }
+        };
+
+        let layer_dir = work_dir.join(format!("layer{}", layers.len() + 1));
+        tokio::fs::create_dir_all(&layer_dir).await?;
+        let extracted = select_backend(&current)
+            .extract_one(&current, &inner.path, &layer_dir, password)
+            .await?;
+
+        // Inner layers may not share the outer password, in which case the outer layer is kept
+        let Ok(inner_entries) = select_backend(&extracted).list(&extracted, password).await else {
+            break;
+        };
+
+        layers.push(inner.path.clone());
+        current = extracted;
+        entries = inner_entries;
+    }
+
+    Ok(UnwrappedArchive {
+        path: current,
+        layers,
+        entries,
+        _temp_dir: temp_dir,
+    })
+}
@@ -0,0 +1,117 @@
+use super::{ArchiveEntry, resolve_volume_path, select_backend};
+use crate::configs::{ConfigFile, Vault};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tempfile::tempdir;
+use ts_rs::TS;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveEncryption {
+    None,
+    // Some entries are encrypted, names and sizes are readable
+    Partial,
+    // Every file is encrypted, names and sizes are readable
+    Full,
+    // The listing itself is encrypted
+    Header,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct PasswordProbe {
+    pub encryption: ArchiveEncryption,
+    // The password that opened the archive, `None` when the user has to be asked
+    pub password: Option<String>,
+}
+
+struct Encryption {
+    kind: ArchiveEncryption,
+    // Smallest encrypted file, used to check candidates without extracting much
+    sample: Option<ArchiveEntry>,
+}
+
+async fn detect_encryption(archive: &Path) -> Result<Encryption> {
+    let entries = match select_backend(archive).list(archive, Some("")).await {
+        Ok(entries) => entries,
+        Err(e) if e.to_string() == "Wrong password" => {
+            return Ok(Encryption {
+                kind: ArchiveEncryption::Header,
+                sample: None,
+            });
+        }
+        Err(e) => return Err(e),
+    };
+
+    let files: Vec<&ArchiveEntry> = entries.iter().filter(|entry| !entry.is_dir).collect();
+    let encrypted = files.iter().filter(|entry| entry.encrypted).count();
+    let kind = match encrypted {
+        0 => ArchiveEncryption::None,
+        n if n == files.len() => ArchiveEncryption::Full,
+        _ => ArchiveEncryption::Partial,
+    };
+    let sample = files
+        .into_iter()
+        .filter(|entry| entry.encrypted)
+        .min_by_key(|entry| entry.size)
+        .cloned();
+
+    Ok(Encryption { kind, sample })
+}
+
+async fn opens_with(archive: &Path, encryption: &Encryption, password: &str) -> bool {
+    let backend = select_backend(archive);
+    match &encryption.sample {
+        Some(sample) => {
+            let Ok(temp_dir) = tempdir() else {
+                return false;
+            };
+            backend
+                .extract_one(archive, &sample.path, temp_dir.path(), Some(password))
+                .await
+                .is_ok()
+        }
+        None => backend.list(archive, Some(password)).await.is_ok(),
+    }
+}
+
+// Checks `candidate` when given, otherwise tries saved passwords, starting with those used for
+// archives from the same place as `source` (a download URL or the archive path).
+// A password that works is remembered so the next archive from the same place opens silently.
+pub async fn resolve_archive_password(
+    path: &str,
+    candidate: Option<&str>,
+    source: &str,
+) -> Result<PasswordProbe> {
+    let archive = resolve_volume_path(Path::new(path))?;
+    let encryption = detect_encryption(&archive).await?;
+    if encryption.kind == ArchiveEncryption::None {
+        return Ok(PasswordProbe {
+            encryption: encryption.kind,
+            password: Some(String::new()),
+        });
+    }
+
+    let mut vault = Vault::read().await?;
+    let candidates = match candidate {
+        Some(candidate) => vec![candidate.to_owned()],
+        None => vault.passwords(source).await?,
+    };
+
+    for password in candidates {
+        if opens_with(&archive, &encryption, &password).await {
+            vault.remember(&password, source).await?;
+            return Ok(PasswordProbe {
+                encryption: encryption.kind,
+                password: Some(password),
+            });
+        }
+    }
+
+    Ok(PasswordProbe {
+        encryption: encryption.kind,
+        password: None,
+    })
+}
@@ -0,0 +1,443 @@
+use super::{ArchiveEntry, EntryFilter, ExtractionFilter, open_payload, sanitize_path};
+use crate::configs::{ConfigFile, settings::Settings};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use ts_rs::TS;
+
+// Device names Windows resolves in any directory, including the superscript digits it treats as 1-3
+const RESERVED_NAMES: [&str; 32] = [
+    "CON",
+    "PRN",
+    "AUX",
+    "NUL",
+    "CONIN$",
+    "CONOUT$",
+    "COM0",
+    "COM1",
+    "COM2",
+    "COM3",
+    "COM4",
+    "COM5",
+    "COM6",
+    "COM7",
+    "COM8",
+    "COM9",
+    "COM\u{B9}",
+    "COM\u{B2}",
+    "COM\u{B3}",
+    "LPT0",
+    "LPT1",
+    "LPT2",
+    "LPT3",
+    "LPT4",
+    "LPT5",
+    "LPT6",
+    "LPT7",
+    "LPT8",
+    "LPT9",
+    "LPT\u{B9}",
+    "LPT\u{B2}",
+    "LPT\u{B3}",
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyViolationKind {
+    AbsolutePath,
+    DrivePrefix,
+    ParentTraversal,
+    EscapingLink,
+    ReservedName,
+    AlternateDataStream,
+    TrailingDotOrSpace,
+    CaseCollision,
+    PathTooLong,
+}
+
+// Rules applied to an entry list before anything is written. Every violation blocks the
+// extraction unless its kind is listed in `report_only`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct ExtractionPolicy {
+    pub max_path_length: u32,
+    pub report_only: Vec<PolicyViolationKind>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct PolicyViolation {
+    pub path: String,
+    pub kind: PolicyViolationKind,
+    pub detail: String,
+    pub blocking: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct PolicyReport {
+    pub violations: Vec<PolicyViolation>,
+}
+
+impl Default for ExtractionPolicy {
+    fn default() -> Self {
+        Self {
+            max_path_length: 260,
+            report_only: Vec::new(),
+        }
+    }
+}
+
+impl PolicyReport {
+    pub fn is_blocking(&self) -> bool {
+        self.violations.iter().any(|violation| violation.blocking)
+    }
+
+    pub fn summary(&self) -> String {
+        let blocking: Vec<_> = self
+            .violations
+            .iter()
+            .filter(|violation| violation.blocking)
+            .collect();
+        let mut summary = blocking
+            .iter()
+            .take(3)
+            .map(|violation| format!("{} ({})", violation.path, violation.detail))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if blocking.len() > 3 {
+            summary.push_str(&format!(" and {} more", blocking.len() - 3));
+        }
+        summary
+    }
+}
+
+impl ExtractionPolicy {
+    // `filter` places the entries below `destination` the way the extraction will
+    pub fn check(
+        &self,
+        entries: &[ArchiveEntry],
+        destination: &Path,
+        filter: &EntryFilter,
+    ) -> PolicyReport {
+        let mut report = PolicyReport::default();
+        let mut push = |path: &str, kind: PolicyViolationKind, detail: String| {
+            report.violations.push(PolicyViolation {
+                path: path.to_owned(),
+                kind,
+                detail,
+                blocking: !self.report_only.contains(&kind),
+            });
+        };
+
+        let links: Vec<&ArchiveEntry> = entries
+            .iter()
+            .filter(|entry| entry.link_target.is_some())
+            .collect();
+        let mut seen: HashMap<String, String> = HashMap::new();
+
+        for entry in entries {
+            let path = entry.path.replace('/', "\\");
+
+            if path.starts_with('\\') {
+                push(
+                    &entry.path,
+                    PolicyViolationKind::AbsolutePath,
+                    "Absolute path".to_owned(),
+                );
+            }
+
+            let components: Vec<&str> = path.split('\\').filter(|part| !part.is_empty()).collect();
+            for (index, component) in components.iter().enumerate() {
+                check_component(&entry.path, index, component, &mut push);
+            }
+
+            if let Some(target) = &entry.link_target {
+                // How far up a link may point depends on where it lands below the filter root
+                let landing = filter.target(&entry.path).unwrap_or_default();
+                let landing: Vec<&str> = landing
+                    .split('\\')
+                    .filter(|part| !part.is_empty())
+                    .collect();
+                if escapes_root(&landing, target) {
+                    push(
+                        &entry.path,
+                        PolicyViolationKind::EscapingLink,
+                        format!("Link points outside the destination: {target}"),
+                    );
+                }
+            }
+            if let Some(link) = links.iter().find(|link| {
+                entry
+                    .path
+                    .get(..link.path.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&link.path))
+                    && entry
+                        .path
+                        .get(link.path.len()..)
+                        .is_some_and(|rest| rest.starts_with('\\'))
+            }) {
+                push(
+                    &entry.path,
+                    PolicyViolationKind::EscapingLink,
+                    format!("Written through the link {}", link.path),
+                );
+            }
+
+            let relative = sanitize_path(&entry.path);
+            if relative.is_empty() {
+                continue;
+            }
+            let length = filter.target(&entry.path).map(|target| {
+                destination
+                    .join(target)
+                    .to_string_lossy()
+                    .encode_utf16()
+                    .count()
+            });
+            if let Some(length) = length
+                && length > self.max_path_length as usize
+            {
+                push(
+                    &entry.path,
+                    PolicyViolationKind::PathTooLong,
+                    format!(
+                        "Path is {length} characters, the limit is {}",
+                        self.max_path_length
+                    ),
+                );
+            }
+
+            match seen.get(&relative.to_lowercase()) {
+                Some(previous) if *previous != relative && !entry.is_dir => push(
+                    &entry.path,
+                    PolicyViolationKind::CaseCollision,
+                    format!("Collides with {previous} on a case-insensitive file system"),
+                ),
+                Some(_) => {}
+                None => {
+                    seen.insert(relative.to_lowercase(), relative);
+                }
+            }
+        }
+
+        report
+    }
+}
+
+fn check_component(
+    path: &str,
+    index: usize,
+    component: &str,
+    push: &mut impl FnMut(&str, PolicyViolationKind, String),
+) {
+    if component == ".." {
+        push(
+            path,
+            PolicyViolationKind::ParentTraversal,
+            "Contains a '..' component".to_owned(),
+        );
+        return;
+    }
+    if component == "." {
+        return;
+    }
+
+    let bytes = component.as_bytes();
+    let is_drive =
+        index == 0 && bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if is_drive {
+        push(
+            path,
+            PolicyViolationKind::DrivePrefix,
+            format!("Starts with the drive {}", &component[..2]),
+        );
+        return;
+    }
+    if component.contains(':') {
+        push(
+            path,
+            PolicyViolationKind::AlternateDataStream,
+            format!("'{component}' names an alternate data stream"),
+        );
+    }
+
+    if component.ends_with(['.', ' ']) {
+        push(
+            path,
+            PolicyViolationKind::TrailingDotOrSpace,
+            format!("'{component}' ends with a dot or space"),
+        );
+    }
+
+    let stem = component
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches([' ', '.']);
+    if RESERVED_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(stem))
+    {
+        push(
+            path,
+            PolicyViolationKind::ReservedName,
+            format!("'{component}' is a reserved device name"),
+        );
+    }
+}
+
+// Resolves a link target relative to the link's own directory and checks it stays inside the root
+fn escapes_root(link_components: &[&str], target: &str) -> bool {
+    let target = target.replace('/', "\\");
+    if target.starts_with('\\') || target.as_bytes().get(1) == Some(&b':') {
+        return true;
+    }
+
+    let mut depth = link_components.len().saturating_sub(1) as i64;
+    for part in target.split('\\') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                depth -= 1;
+                if depth < 0 {
+                    return true;
+                }
+            }
+            _ => depth += 1,
+        }
+    }
+    false
+}
+
+// Checks an archive against the configured policy without extracting it
+pub async fn check_extraction_policy(
+    path: &str,
+    password: Option<&str>,
+    destination: &str,
+    filter: &ExtractionFilter,
+) -> Result<PolicyReport> {
+    let filter = filter.compile()?;
+    let policy = Settings::read().await?.archive.extraction_policy;
+    let payload = open_payload(path, password).await?;
+    let selected: Vec<ArchiveEntry> = payload
+        .entries
+        .into_iter()
+        .filter(|entry| filter.target(&entry.path).is_some())
+        .collect();
+    Ok(policy.check(&selected, Path::new(destination), &filter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> ArchiveEntry {
+        ArchiveEntry {
+            path: path.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn kinds(report: &PolicyReport) -> Vec<PolicyViolationKind> {
+        report
+            .violations
+            .iter()
+            .map(|violation| violation.kind)
+            .collect()
+    }
+
+    #[test]
+    fn flags_every_reserved_device_name() {
+        let policy = ExtractionPolicy::default();
+        for name in [
+            "com0.txt",
+            "LPT0",
+            "conin$",
+            "CONOUT$.log",
+            "COM\u{B9}",
+            "lpt\u{B3}.dat",
+        ] {
+            let report = policy.check(
+                &[entry(&format!("app\\{name}"))],
+                Path::new("C:\\Apps"),
+                &EntryFilter::default(),
+            );
+            assert_eq!(
+                kinds(&report),
+                [PolicyViolationKind::ReservedName],
+                "{name}"
+            );
+        }
+
+        let report = policy.check(
+            &[entry("app\\COM10.txt"), entry("app\\console.txt")],
+            Path::new("C:\\Apps"),
+            &EntryFilter::default(),
+        );
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn checks_links_where_they_land_below_the_filter_root() {
+        let policy = ExtractionPolicy::default();
+        let link = |path: &str, target: &str| ArchiveEntry {
+            link_target: Some(target.to_owned()),
+            ..entry(path)
+        };
+        let entries = [
+            link("wrapper\\link", ".."),
+            link("wrapper\\bin\\current", "..\\readme.txt"),
+        ];
+
+        // Inside the archive both links stay below the top folder
+        let report = policy.check(&entries, Path::new("C:\\Apps"), &EntryFilter::default());
+        assert!(report.violations.is_empty());
+
+        // With the wrapper as the root, `link` lands at the top and points above the destination
+        let filter = ExtractionFilter {
+            root: "wrapper".to_owned(),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        let report = policy.check(&entries, Path::new("C:\\Apps"), &filter);
+        let escaping: Vec<&str> = report
+            .violations
+            .iter()
+            .filter(|violation| violation.kind == PolicyViolationKind::EscapingLink)
+            .map(|violation| violation.path.as_str())
+            .collect();
+        assert_eq!(escaping, ["wrapper\\link"]);
+    }
+
+    #[test]
+    fn measures_the_path_below_the_filter_root() {
+        let policy = ExtractionPolicy {
+            max_path_length: 40,
+            report_only: Vec::new(),
+        };
+        let deep = format!("{}\\bin\\tool.exe", "wrapper".repeat(4));
+        let entries = [entry(&deep)];
+        // C:\Apps\Tool\ plus the whole entry path is over the limit
+        let report = policy.check(
+            &entries,
+            Path::new("C:\\Apps\\Tool"),
+            &EntryFilter::default(),
+        );
+        assert_eq!(kinds(&report), [PolicyViolationKind::PathTooLong]);
+
+        // Only bin\tool.exe lands below the destination once the wrapper folder is the root
+        let filter = ExtractionFilter {
+            root: "wrapper".repeat(4),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+        let report = policy.check(&entries, Path::new("C:\\Apps\\Tool"), &filter);
+        assert!(report.violations.is_empty());
+    }
+}
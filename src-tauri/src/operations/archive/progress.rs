@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use ts_rs::TS;
+
+pub const EXTRACT_PROGRESS_EVENT: &str = "extract_progress";
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractTask {
+    AppInstall,
+    ToolInstall,
+    AppReinstall,
+    ToolReinstall,
+    Installer,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ExtractProgress {
+    pub task: ExtractTask,
+    pub percent: u32,
+    #[ts(type = "number")]
+    pub bytes_done: u64,
+    #[ts(type = "number")]
+    pub bytes_total: u64,
+    #[ts(type = "number")]
+    pub files_done: u64,
+    #[ts(type = "number")]
+    pub files_total: u64,
+    pub current_file: String,
+    #[ts(type = "number")]
+    pub bytes_per_second: u64,
+    #[ts(type = "number | null")]
+    pub eta_seconds: Option<u64>,
+}
+
+struct ReporterState {
+    progress: ExtractProgress,
+    started: Instant,
+    last_emit: Option<Instant>,
+}
+
+type ProgressSink = Arc<dyn Fn(&ExtractProgress) + Send + Sync>;
+
+// Collects raw counters from a backend and turns them into throttled `ExtractProgress` events
+#[derive(Clone)]
+pub struct ProgressReporter {
+    state: Arc<Mutex<ReporterState>>,
+    sink: ProgressSink,
+}
+
+impl ProgressReporter {
+    pub fn new(task: ExtractTask, sink: impl Fn(&ExtractProgress) + Send + Sync + 'static) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ReporterState {
+                progress: ExtractProgress {
+                    task,
+                    percent: 0,
+                    bytes_done: 0,
+                    bytes_total: 0,
+                    files_done: 0,
+                    files_total: 0,
+                    current_file: String::new(),
+                    bytes_per_second: 0,
+                    eta_seconds: None,
+                },
+                started: Instant::now(),
+                last_emit: None,
+            })),
+            sink: Arc::new(sink),
+        }
+    }
+
+    pub fn for_app(app: &AppHandle, task: ExtractTask) -> Self {
+        let app = app.clone();
+        Self::new(task, move |progress| {
+            let _ = app.emit(EXTRACT_PROGRESS_EVENT, progress);
+        })
+    }
+
+    pub fn set_totals(&self, bytes_total: u64, files_total: u64) {
+        if let Ok(mut state) = self.state.lock() {
+            state.progress.bytes_total = bytes_total;
+            state.progress.files_total = files_total;
+        }
+    }
+
+    pub fn update(&self, bytes_done: u64, files_done: u64, current_file: &str) {
+        let percent = self.with_state(|state| {
+            (bytes_done * 100)
+                .checked_div(state.progress.bytes_total)
+                .unwrap_or(0)
+        });
+        self.report(
+            percent.min(100) as u32,
+            bytes_done,
+            files_done,
+            current_file,
+        );
+    }
+
+    // For backends that only know a percentage, the byte count is derived from the totals
+    pub fn update_percent(&self, percent: u32, files_done: u64, current_file: &str) {
+        let bytes_done =
+            self.with_state(|state| state.progress.bytes_total * u64::from(percent.min(100)) / 100);
+        self.report(percent.min(100), bytes_done, files_done, current_file);
+    }
+
+    pub fn finish(&self) {
+        let (bytes_total, files_total) =
+            self.with_state(|state| (state.progress.bytes_total, state.progress.files_total));
+        if let Ok(mut state) = self.state.lock() {
+            state.last_emit = None;
+        }
+        self.report(100, bytes_total, files_total, "");
+    }
+
+    fn with_state<T: Default>(&self, f: impl FnOnce(&ReporterState) -> T) -> T {
+        self.state.lock().map(|state| f(&state)).unwrap_or_default()
+    }
+
+    fn report(&self, percent: u32, bytes_done: u64, files_done: u64, current_file: &str) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        let now = Instant::now();
+        let percent_changed = percent != state.progress.percent;
+        let throttled = state
+            .last_emit
+            .is_some_and(|last| now.duration_since(last) < Duration::from_millis(250));
+
+        let elapsed = now.duration_since(state.started).as_secs_f64();
+        let bytes_per_second = if elapsed > 0.0 {
+            (bytes_done as f64 / elapsed) as u64
+        } else {
+            0
+        };
+
+        let progress = &mut state.progress;
+        progress.percent = percent;
+        progress.bytes_done = bytes_done;
+        progress.files_done = files_done;
+        progress.current_file = current_file.to_owned();
+        progress.bytes_per_second = bytes_per_second;
+        progress.eta_seconds = (bytes_per_second > 0 && progress.bytes_total >= bytes_done)
+            .then(|| (progress.bytes_total - bytes_done) / bytes_per_second);
+
+        if percent_changed || !throttled {
+            state.last_emit = Some(now);
+            (self.sink)(&state.progress);
+        }
+    }
+}
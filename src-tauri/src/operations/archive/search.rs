@@ -0,0 +1,105 @@
+use super::{ArchiveEntry, open_payload, saved_password};
+use anyhow::{Result, anyhow};
+use globset::GlobBuilder;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ArchiveSearchMatch {
+    pub entry: ArchiveEntry,
+    // Keys of the enclosing tree directories, outermost first
+    pub parents: Vec<String>,
+}
+
+enum SearchPattern {
+    Glob(globset::GlobMatcher),
+    Regex(regex::Regex),
+}
+
+impl SearchPattern {
+    // `/.../` is a regex, anything else a glob. A glob without `/` matches file names only,
+    // one with `/` matches path tails at any depth, and one without wildcards matches any
+    // name containing it.
+    fn parse(query: &str) -> Result<(Self, bool)> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(anyhow!("Search query is empty"));
+        }
+
+        if let Some(pattern) = query
+            .strip_prefix('/')
+            .and_then(|query| query.strip_suffix('/'))
+            .filter(|pattern| !pattern.is_empty())
+        {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| anyhow!("Invalid regex '{}': {}", pattern, e))?;
+            return Ok((Self::Regex(regex), true));
+        }
+
+        let pattern = query.replace('\\', "/");
+        let whole_path = pattern.contains('/');
+        let pattern = if !pattern.contains(['*', '?', '[', '{']) {
+            format!("*{pattern}*")
+        } else if whole_path && !pattern.starts_with("**/") {
+            format!("**/{}", pattern.trim_start_matches('/'))
+        } else {
+            pattern
+        };
+        let glob = GlobBuilder::new(&pattern)
+            .case_insensitive(true)
+            .literal_separator(whole_path)
+            .build()
+            .map_err(|e| anyhow!("Invalid glob '{}': {}", pattern, e))?;
+        Ok((Self::Glob(glob.compile_matcher()), whole_path))
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        match self {
+            Self::Glob(glob) => glob.is_match(text),
+            Self::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+pub fn search_entries(entries: Vec<ArchiveEntry>, query: &str) -> Result<Vec<ArchiveSearchMatch>> {
+    let (pattern, whole_path) = SearchPattern::parse(query)?;
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let parts: Vec<&str> = entry.path.split('\\').filter(|p| !p.is_empty()).collect();
+            let name = parts.last()?;
+            let matched = if whole_path {
+                pattern.is_match(&parts.join("/"))
+            } else {
+                pattern.is_match(name)
+            };
+            if !matched {
+                return None;
+            }
+
+            // Same keys `build_file_tree` gives the directories
+            let parents = (1..parts.len())
+                .map(|depth| parts[..depth].join("\\"))
+                .collect();
+            Some(ArchiveSearchMatch { entry, parents })
+        })
+        .collect())
+}
+
+pub async fn search_archive(
+    path: &str,
+    password: Option<&str>,
+    query: &str,
+) -> Result<Vec<ArchiveSearchMatch>> {
+    let password = match password {
+        Some(password) => password.to_owned(),
+        None => saved_password(path).await?,
+    };
+    let payload = open_payload(path, Some(&password)).await?;
+    search_entries(payload.entries, query)
+}
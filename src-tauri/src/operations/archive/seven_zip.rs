@@ -0,0 +1,515 @@
+use super::{
+    ArchiveBackend, ArchiveEntry, ArchiveTestReport, EntryFilter, EntryTestFailure,
+    MODIFIED_FORMAT, ProgressReporter, join_entry_path, normalize_entry_path, sanitize_path,
+};
+use anyhow::{Result, anyhow};
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+use std::env;
+use std::io::Read;
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use tokio::fs;
+use tokio::process::Command;
+
+pub async fn get_7z_path() -> Result<PathBuf> {
+    let current_exe = env::current_exe()?;
+    let current_dir = current_exe
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get exe directory"))?;
+
+    let files = vec![
+        (
+            "7z.dll",
+            include_bytes!("../../../resources/7z.dll").as_slice(),
+        ),
+        (
+            "7z.exe",
+            include_bytes!("../../../resources/7z.exe").as_slice(),
+        ),
+    ];
+
+    for (filename, bytes) in &files {
+        let file_path = current_dir.join(filename);
+        if !file_path.exists() || fs::metadata(&file_path).await?.len() != bytes.len() as u64 {
+            fs::write(&file_path, bytes).await?;
+        }
+    }
+
+    Ok(current_dir.join("7z.exe"))
+}
+
+// Fallback backend driving the bundled 7-Zip CLI, used for formats without a native reader
+pub struct SevenZipBackend;
+
+impl SevenZipBackend {
+    async fn run(args: &[&str]) -> Result<Output> {
+        let output = Command::new(get_7z_path().await?)
+            .args(args)
+            .creation_flags(0x08000000)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let error_str = String::from_utf8_lossy(&output.stderr);
+            if error_str.contains("Cannot open encrypted archive. Wrong password?")
+                || error_str.contains("Wrong password")
+            {
+                return Err(anyhow!("Wrong password"));
+            }
+            return Err(anyhow!("{}", error_str));
+        }
+
+        Ok(output)
+    }
+}
+
+#[async_trait::async_trait]
+impl ArchiveBackend for SevenZipBackend {
+    async fn list(&self, archive: &Path, password: Option<&str>) -> Result<Vec<ArchiveEntry>> {
+        let password_arg = format!("-p{}", password.unwrap_or_default());
+        let output = Self::run(&[
+            "l",
+            "-slt",
+            "-sccUTF-8",
+            &archive.to_string_lossy(),
+            "-y",
+            &password_arg,
+        ])
+        .await?;
+        Ok(parse_7z_slt_output(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    async fn extract(
+        &self,
+        archive: &Path,
+        destination: &Path,
+        password: Option<&str>,
+        filter: &EntryFilter,
+        progress: Option<ProgressReporter>,
+    ) -> Result<()> {
+        let mut entries = if progress.is_some() || !filter.is_passthrough() {
+            self.list(archive, password).await?
+        } else {
+            Vec::new()
+        };
+        entries.retain(|entry| filter.target(&entry.path).is_some());
+        if let Some(progress) = &progress {
+            progress.set_totals(
+                entries.iter().map(|entry| entry.size).sum(),
+                entries.iter().filter(|entry| !entry.is_dir).count() as u64,
+            );
+        }
+
+        // 7-Zip cannot rebase paths, so filtered extractions go through a staging directory.
+        // The selected files are named in a list file so that nothing else gets written.
+        let staging = if filter.is_passthrough() {
+            None
+        } else {
+            Some(tempfile::tempdir_in(destination)?)
+        };
+        let list_file = match &staging {
+            Some(_) => {
+                let selected: Vec<&str> = entries
+                    .iter()
+                    .filter(|entry| !entry.is_dir)
+                    .map(|entry| entry.path.as_str())
+                    .collect();
+                let list_file = tempfile::NamedTempFile::new()?;
+                std::fs::write(list_file.path(), selected.join("\n"))?;
+                Some((list_file, selected.is_empty()))
+            }
+            None => None,
+        };
+        let output_root = staging
+            .as_ref()
+            .map(|dir| dir.path())
+            .unwrap_or(destination);
+
+        let archive = archive.to_string_lossy();
+        let output_dir = format!("-o{}", output_root.to_string_lossy());
+        let password_arg = format!("-p{}", password.unwrap_or_default());
+        let list_arg = list_file
+            .as_ref()
+            .map(|(file, _)| format!("@{}", file.path().to_string_lossy()));
+        let mut extract_args = vec![
+            "-bsp2",
+            "-sccUTF-8",
+            "-scsUTF-8",
+            "x",
+            &archive,
+            &output_dir,
+            "-y",
+            "-aoa",
+            "-snl",
+            &password_arg,
+        ];
+        if let Some(list_arg) = &list_arg {
+            // Names in the list are literal paths, not wildcards
+            extract_args.extend(["-spd", list_arg.as_str()]);
+        }
+
+        // An empty list would mean everything, a filter selecting folders only has no files to extract
+        if !list_file.as_ref().is_some_and(|(_, empty)| *empty) {
+            run_with_progress(&get_7z_path().await?, &extract_args, progress.clone())?;
+        }
+
+        if let Some(staging) = &staging {
+            move_selected(staging.path(), destination, &entries, filter).await?;
+        }
+
+        if let Some(progress) = &progress {
+            progress.finish();
+        }
+        Ok(())
+    }
+
+    async fn extract_one(
+        &self,
+        archive: &Path,
+        entry: &str,
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<PathBuf> {
+        let output_dir = format!("-o{}", destination.to_string_lossy());
+        let password_arg = format!("-p{}", password.unwrap_or_default());
+        Self::run(&[
+            "e",
+            &archive.to_string_lossy(),
+            entry,
+            &output_dir,
+            "-y",
+            "-aoa",
+            "-snl",
+            &password_arg,
+        ])
+        .await?;
+
+        let sanitized = sanitize_path(entry);
+        let file_name = Path::new(&sanitized)
+            .file_name()
+            .ok_or(anyhow!("Failed to get file name"))?;
+        Ok(destination.join(file_name))
+    }
+
+    async fn extract_many(
+        &self,
+        archive: &Path,
+        entries: &[String],
+        destination: &Path,
+        password: Option<&str>,
+    ) -> Result<()> {
+        // 7-Zip would take an empty list as "everything"
+        if entries.is_empty() {
+            return Ok(());
+        }
+        // Named in a list file and taken as literal paths, so `*` or `?` in a name match nothing else
+        let list_file = tempfile::NamedTempFile::new()?;
+        std::fs::write(list_file.path(), entries.join("\n"))?;
+
+        let archive = archive.to_string_lossy();
+        let output_dir = format!("-o{}", destination.to_string_lossy());
+        let password_arg = format!("-p{}", password.unwrap_or_default());
+        let list_arg = format!("@{}", list_file.path().to_string_lossy());
+        Self::run(&[
+            "-scsUTF-8",
+            "x",
+            &archive,
+            &output_dir,
+            "-y",
+            "-aoa",
+            "-snl",
+            &password_arg,
+            "-spd",
+            &list_arg,
+        ])
+        .await?;
+        Ok(())
+    }
+
+    async fn test(&self, archive: &Path, password: Option<&str>) -> Result<ArchiveTestReport> {
+        let password_arg = format!("-p{}", password.unwrap_or_default());
+        // Failed entries make 7-Zip exit non-zero, so the output is parsed regardless of status
+        let output = Command::new(get_7z_path().await?)
+            .args([
+                "t",
+                "-sccUTF-8",
+                &archive.to_string_lossy(),
+                "-y",
+                &password_arg,
+            ])
+            .creation_flags(0x08000000)
+            .output()
+            .await?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("Cannot open encrypted archive. Wrong password?") {
+            return Err(anyhow!("Wrong password"));
+        }
+        Ok(parse_test_output(&format!("{stdout}\n{stderr}")))
+    }
+}
+
+// Runs 7-Zip with `-bsp2`, forwarding the progress it prints on stderr
+fn run_with_progress(
+    seven_zip: &Path,
+    args: &[&str],
+    progress: Option<ProgressReporter>,
+) -> Result<()> {
+    let mut child = std::process::Command::new(seven_zip)
+        .args(args)
+        .creation_flags(0x08000000)
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut stderr = child
+        .stderr
+        .take()
+        .ok_or(anyhow!("Failed to capture stderr"))?;
+    let mut buffer = [0; 1024];
+
+    let handle = std::thread::spawn(move || {
+        while let Ok(n) = stderr.read(&mut buffer) {
+            if n == 0 {
+                break;
+            }
+            if let Some(reporter) = &progress {
+                for line in String::from_utf8_lossy(&buffer[..n]).split(['\u{8}', '\r', '\n']) {
+                    if let Some((percent, files_done, current_file)) = parse_progress_line(line) {
+                        reporter.update_percent(percent, files_done, current_file);
+                    }
+                }
+            }
+        }
+    });
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("7-Zip extraction failed"));
+    }
+    handle.join().map_err(|_| anyhow!("Thread join failed"))?;
+    Ok(())
+}
+
+async fn move_selected(
+    staging: &Path,
+    destination: &Path,
+    entries: &[ArchiveEntry],
+    filter: &EntryFilter,
+) -> Result<()> {
+    for entry in entries {
+        let Some(relative) = filter.target(&entry.path) else {
+            continue;
+        };
+        let source = join_entry_path(staging, &sanitize_path(&entry.path));
+        let target = join_entry_path(destination, &relative);
+
+        if entry.is_dir {
+            fs::create_dir_all(&target).await?;
+            continue;
+        }
+        if !source.exists() {
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(&source, &target).await?;
+    }
+    Ok(())
+}
+
+// Collects `ERROR: <reason> : <path>` lines and the `Files: <n>` summary of a `t` run
+fn parse_test_output(output: &str) -> ArchiveTestReport {
+    let mut report = ArchiveTestReport::default();
+    for line in output.lines().map(str::trim) {
+        if let Some(files) = line.strip_prefix("Files: ") {
+            report.tested = files.trim().parse().unwrap_or_default();
+        } else if let Some(error) = line.strip_prefix("ERROR: ") {
+            let (reason, path) = error.rsplit_once(" : ").unwrap_or((error, ""));
+            report.failures.push(EntryTestFailure {
+                path: normalize_entry_path(path),
+                reason: reason.trim().to_owned(),
+            });
+        }
+    }
+    report
+}
+
+// Parses a `-bsp2` status line such as ` 42% 17 - bin\app.exe`
+fn parse_progress_line(line: &str) -> Option<(u32, u64, &str)> {
+    let (percent, rest) = line.trim().split_once('%')?;
+    let percent = percent.trim().parse::<u32>().ok()?;
+    let (files_done, current_file) = rest.split_once(" - ").unwrap_or((rest, ""));
+    Some((
+        percent,
+        files_done.trim().parse().unwrap_or_default(),
+        current_file.trim(),
+    ))
+}
+
+// Parses the `-slt` technical listing, where every entry is a block of `Key = Value` lines
+pub fn parse_7z_slt_output(output: &str) -> Vec<ArchiveEntry> {
+    let mut result = Vec::new();
+    let Some((_, listing)) = output.split_once("\n----------") else {
+        return result;
+    };
+
+    let mut fields = HashMap::new();
+    for line in listing.lines().chain(std::iter::once("")) {
+        let line = line.trim_end_matches('\r');
+        if let Some((key, value)) = line.split_once(" = ") {
+            fields.insert(key.trim(), value.trim());
+            continue;
+        }
+        if !line.trim().is_empty() || fields.is_empty() {
+            continue;
+        }
+
+        if let Some(entry) = slt_entry(&fields) {
+            result.push(entry);
+        }
+        fields.clear();
+    }
+    result
+}
+
+fn slt_entry(fields: &HashMap<&str, &str>) -> Option<ArchiveEntry> {
+    let path = normalize_entry_path(fields.get("Path")?);
+    if path.is_empty() {
+        return None;
+    }
+
+    let attributes = fields.get("Attributes").copied().unwrap_or_default();
+    let number = |key: &str| {
+        fields
+            .get(key)
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or_default()
+    };
+
+    Some(ArchiveEntry {
+        path,
+        is_dir: fields.get("Folder") == Some(&"+") || attributes.starts_with('D'),
+        size: number("Size"),
+        packed_size: number("Packed Size"),
+        modified: fields
+            .get("Modified")
+            .and_then(|value| value.get(..19))
+            .and_then(|value| NaiveDateTime::parse_from_str(value, MODIFIED_FORMAT).ok())
+            .map(|time| time.format(MODIFIED_FORMAT).to_string())
+            .unwrap_or_default(),
+        crc: fields
+            .get("CRC")
+            .and_then(|value| u32::from_str_radix(value, 16).ok()),
+        attributes: attributes
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_owned(),
+        encrypted: fields.get("Encrypted") == Some(&"+"),
+        link_target: fields
+            .get("Symbolic Link")
+            .or(fields.get("Hard Link"))
+            .filter(|target| !target.is_empty())
+            .map(|target| target.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLT_OUTPUT: &str = "\
+7-Zip 24.09 (x64) : Copyright (c) 1999-2024 Igor Pavlov : 2024-11-29\r
+\r
+Scanning the drive for archives:\r
+1 file, 4096 bytes (4 KiB)\r
+\r
+Listing archive: sample.rar\r
+\r
+--\r
+Path = sample.rar\r
+Type = Rar5\r
+Physical Size = 4096\r
+\r
+----------\r
+Path = app\r
+Folder = +\r
+Size = 0\r
+Packed Size = 0\r
+Modified = 2024-03-15 12:30:00.1234567\r
+Attributes = D\r
+Encrypted = -\r
+\r
+Path = app\\bin\\tool.exe\r
+Folder = -\r
+Size = 16\r
+Packed Size = 12\r
+Modified = 2024-03-15 12:30:00.1234567\r
+Attributes = A -rw-r--r--\r
+CRC = 1A2B3C4D\r
+Encrypted = +\r
+\r
+Path = app\\current\r
+Folder = -\r
+Size = 0\r
+Packed Size = 0\r
+Attributes = A\r
+Symbolic Link = bin\r
+\r
+Path = app\\readme.txt\r
+Folder = -\r
+Size = 23\r
+Packed Size = 23\r
+Modified = 2024-03-15 12:30:00\r
+";
+
+    #[test]
+    fn parses_the_technical_listing() {
+        let entries = parse_7z_slt_output(SLT_OUTPUT);
+        let paths: Vec<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+        // The archive's own block before the separator is not an entry
+        assert_eq!(
+            paths,
+            [
+                "app",
+                "app\\bin\\tool.exe",
+                "app\\current",
+                "app\\readme.txt"
+            ]
+        );
+
+        assert!(entries[0].is_dir);
+        assert_eq!(
+            entries[1],
+            ArchiveEntry {
+                path: "app\\bin\\tool.exe".to_owned(),
+                is_dir: false,
+                size: 16,
+                packed_size: 12,
+                modified: "2024-03-15 12:30:00".to_owned(),
+                crc: Some(0x1A2B_3C4D),
+                attributes: "A".to_owned(),
+                encrypted: true,
+                link_target: None,
+            }
+        );
+        assert_eq!(entries[2].link_target.as_deref(), Some("bin"));
+        // The last block has no trailing blank line
+        assert_eq!(entries[3].size, 23);
+    }
+
+    #[test]
+    fn ignores_output_without_a_listing() {
+        assert!(parse_7z_slt_output("").is_empty());
+        assert!(
+            parse_7z_slt_output("ERROR: sample.rar\r\nCannot open the file as archive\r\n")
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use super::{ArchiveFormat, VolumeSet};
+use crate::SUPPORTED_EXTENSIONS;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use ts_rs::TS;
+
+// How far into a PE overlay to look for an embedded archive
+const OVERLAY_SCAN_LIMIT: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum SniffedFormat {
+    Zip,
+    SevenZip,
+    Rar4,
+    Rar5,
+    Gzip,
+    Xz,
+    Bzip2,
+    Zstd,
+    Cab,
+    Tar,
+    // A PE executable carrying an archive after its last section
+    SelfExtracting,
+}
+
+impl SniffedFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::SevenZip => "7z",
+            Self::Rar4 | Self::Rar5 => "rar",
+            Self::Gzip => "gz",
+            Self::Xz => "xz",
+            Self::Bzip2 => "bz2",
+            Self::Zstd => "zst",
+            Self::Cab => "cab",
+            Self::Tar => "tar",
+            Self::SelfExtracting => "exe",
+        }
+    }
+
+    pub fn archive_format(&self) -> ArchiveFormat {
+        match self {
+            Self::Zip => ArchiveFormat::Zip,
+            Self::SevenZip => ArchiveFormat::SevenZip,
+            Self::Tar => ArchiveFormat::Tar,
+            _ => ArchiveFormat::Other,
+        }
+    }
+}
+
+pub fn sniff_bytes(header: &[u8]) -> Option<SniffedFormat> {
+    const SIGNATURES: [(&[u8], SniffedFormat); 12] = [
+        (b"PK\x03\x04", SniffedFormat::Zip),
+        (b"PK\x05\x06", SniffedFormat::Zip),
+        (b"PK\x07\x08", SniffedFormat::Zip),
+        (b"7z\xBC\xAF\x27\x1C", SniffedFormat::SevenZip),
+        (b"Rar!\x1A\x07\x01\x00", SniffedFormat::Rar5),
+        (b"Rar!\x1A\x07\x00", SniffedFormat::Rar4),
+        (b"\x1F\x8B", SniffedFormat::Gzip),
+        (b"\xFD7zXZ\x00", SniffedFormat::Xz),
+        (b"BZh", SniffedFormat::Bzip2),
+        (b"\x28\xB5\x2F\xFD", SniffedFormat::Zstd),
+        (b"MSCF\x00\x00\x00\x00", SniffedFormat::Cab),
+        (b"MZ", SniffedFormat::SelfExtracting),
+    ];
+
+    if header.get(257..262) == Some(b"ustar") {
+        return Some(SniffedFormat::Tar);
+    }
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| header.starts_with(signature))
+        .map(|(_, format)| *format)
+}
+
+pub fn sniff_file(path: &Path) -> Result<Option<SniffedFormat>> {
+    let mut file = File::open(path)?;
+    let mut header = Vec::with_capacity(512);
+    file.by_ref().take(512).read_to_end(&mut header)?;
+
+    match sniff_bytes(&header) {
+        Some(SniffedFormat::SelfExtracting) => {
+            Ok(has_archive_overlay(&mut file)?.then_some(SniffedFormat::SelfExtracting))
+        }
+        format => Ok(format),
+    }
+}
+
+// Whether a file can be opened as an archive, judged by content rather than by name
+pub fn is_archive_file(path: &Path) -> bool {
+    VolumeSet::is_volume_path(path) || sniff_file(path).is_ok_and(|format| format.is_some())
+}
+
+// Gives downloads saved as `.bin`, `.tmp` or without extension one matching their content
+pub async fn ensure_archive_extension(path: &str) -> Result<String> {
+    let format = sniff_file(Path::new(path))?.ok_or(anyhow!("Unsupported archive format"))?;
+    let has_known_extension = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| ext == "exe" || SUPPORTED_EXTENSIONS.contains(&ext.as_str()));
+    if has_known_extension {
+        return Ok(path.to_owned());
+    }
+
+    let renamed = format!("{}.{}", path, format.extension());
+    tokio::fs::rename(path, &renamed).await?;
+    Ok(renamed)
+}
+
+// The overlay starts where the last PE section ends
+fn has_archive_overlay(file: &mut File) -> Result<bool> {
+    let mut dos_header = [0u8; 64];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut dos_header)?;
+    let pe_offset = u64::from(u32::from_le_bytes(dos_header[60..64].try_into()?));
+
+    let mut pe_header = [0u8; 24];
+    file.seek(SeekFrom::Start(pe_offset))?;
+    file.read_exact(&mut pe_header)?;
+    if &pe_header[..4] != b"PE\0\0" {
+        return Ok(false);
+    }
+    let section_count = u16::from_le_bytes([pe_header[6], pe_header[7]]);
+    let optional_header_size = u16::from_le_bytes([pe_header[20], pe_header[21]]);
+
+    let mut sections = vec![0u8; usize::from(section_count) * 40];
+    file.seek(SeekFrom::Start(
+        pe_offset + 24 + u64::from(optional_header_size),
+    ))?;
+    file.read_exact(&mut sections)?;
+    let image_end = sections
+        .chunks_exact(40)
+        .map(|section| {
+            let raw_size = u32::from_le_bytes(section[16..20].try_into().unwrap_or_default());
+            let raw_offset = u32::from_le_bytes(section[20..24].try_into().unwrap_or_default());
+            u64::from(raw_offset) + u64::from(raw_size)
+        })
+        .max()
+        .unwrap_or_default();
+
+    if image_end >= file.metadata()?.len() {
+        return Ok(false);
+    }
+    let mut overlay = Vec::new();
+    file.seek(SeekFrom::Start(image_end))?;
+    file.take(OVERLAY_SCAN_LIMIT as u64)
+        .read_to_end(&mut overlay)?;
+
+    const EMBEDDED: [&[u8]; 3] = [b"7z\xBC\xAF\x27\x1C", b"Rar!\x1A\x07", b"PK\x03\x04"];
+    Ok(overlay.windows(6).any(|window| {
+        EMBEDDED
+            .iter()
+            .any(|signature| window.starts_with(signature))
+    }))
+}
@@ -0,0 +1,389 @@
+use anyhow::{Result, anyhow};
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeScheme {
+    // `app.7z.001`, `app.7z.002`, ..., a lone `.001` is not a set
+    Numbered,
+    // `app.part1.rar`, `app.part2.rar`, ...
+    RarParts,
+    // `app.z01`, `app.z02`, ..., `app.zip`, where the `.zip` is the last volume
+    ZipSpanned,
+}
+
+#[derive(Debug, Clone)]
+pub struct VolumeSet {
+    pub scheme: VolumeScheme,
+    pub volumes: Vec<PathBuf>,
+}
+
+struct VolumeName {
+    scheme: VolumeScheme,
+    // File name up to the volume number, in the original case
+    base: String,
+    width: usize,
+}
+
+impl VolumeName {
+    fn parse(name: &str) -> Option<Self> {
+        let lower = name.to_ascii_lowercase();
+
+        if let Some((stem, number)) = lower.rsplit_once('.')
+            && number.len() >= 3
+            && number.bytes().all(|b| b.is_ascii_digit())
+            && stem.contains('.')
+        {
+            return Some(Self {
+                scheme: VolumeScheme::Numbered,
+                base: name[..stem.len()].to_owned(),
+                width: number.len(),
+            });
+        }
+
+        if let Some(stem) = lower.strip_suffix(".rar")
+            && let Some((base, number)) = stem.rsplit_once(".part")
+            && !number.is_empty()
+            && number.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Some(Self {
+                scheme: VolumeScheme::RarParts,
+                base: name[..base.len()].to_owned(),
+                width: number.len(),
+            });
+        }
+
+        let (stem, ext) = lower.rsplit_once('.')?;
+        let is_split_part =
+            ext.len() >= 3 && ext.starts_with('z') && ext[1..].bytes().all(|b| b.is_ascii_digit());
+        (is_split_part || ext == "zip").then(|| Self {
+            scheme: VolumeScheme::ZipSpanned,
+            base: name[..stem.len()].to_owned(),
+            width: 2,
+        })
+    }
+
+    fn volume_name(&self, index: u32) -> String {
+        let width = self.width;
+        match self.scheme {
+            VolumeScheme::Numbered => format!("{}.{index:0width$}", self.base),
+            VolumeScheme::RarParts => format!("{}.part{index:0width$}.rar", self.base),
+            VolumeScheme::ZipSpanned => format!("{}.z{index:0width$}", self.base),
+        }
+    }
+
+    fn index_of(&self, file_name: &str) -> Option<u32> {
+        let lower = file_name.to_ascii_lowercase();
+        let rest = lower.strip_prefix(&self.base.to_ascii_lowercase())?;
+        let number = match self.scheme {
+            VolumeScheme::Numbered => rest.strip_prefix('.')?,
+            VolumeScheme::RarParts => rest.strip_prefix(".part")?.strip_suffix(".rar")?,
+            VolumeScheme::ZipSpanned => rest.strip_prefix(".z")?,
+        };
+        if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        number.parse().ok()
+    }
+}
+
+impl VolumeSet {
+    // Cheap name-based check used to route volume sets to a backend that can read them
+    pub fn is_volume_path(path: &Path) -> bool {
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            return false;
+        };
+        match VolumeName::parse(&name) {
+            Some(volume) if volume.scheme == VolumeScheme::ZipSpanned => {
+                path.with_file_name(volume.volume_name(1)).exists()
+            }
+            Some(volume) if volume.scheme == VolumeScheme::Numbered => {
+                volume.index_of(&name) != Some(1)
+                    || path.with_file_name(volume.volume_name(2)).exists()
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    // Finds the siblings of any volume and checks that the set is complete
+    pub fn detect(path: &Path) -> Result<Option<Self>> {
+        if !Self::is_volume_path(path) {
+            return Ok(None);
+        }
+        let name = path
+            .file_name()
+            .ok_or(anyhow!("Failed to get file name"))?
+            .to_string_lossy()
+            .to_string();
+        let volume = VolumeName::parse(&name).ok_or(anyhow!("Failed to parse volume name"))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+
+        let mut found = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if let Some(index) = volume.index_of(&entry.file_name().to_string_lossy()) {
+                found.push((index, entry.path()));
+            }
+        }
+        found.sort_by_key(|(index, _)| *index);
+
+        let missing = |index: u32| anyhow!("Missing archive volume: {}", volume.volume_name(index));
+        for (expected, (index, _)) in (1..).zip(&found) {
+            if *index != expected {
+                return Err(missing(expected));
+            }
+        }
+        let last_index = found.last().map(|(index, _)| *index).unwrap_or_default();
+
+        let mut volumes: Vec<PathBuf> = found.into_iter().map(|(_, path)| path).collect();
+        if volume.scheme == VolumeScheme::ZipSpanned {
+            let zip_path = dir.join(format!("{}.zip", volume.base));
+            if !zip_path.exists() {
+                return Err(anyhow!(
+                    "Missing archive volume: {}",
+                    zip_path.file_name().unwrap_or_default().to_string_lossy()
+                ));
+            }
+            // The end of central directory record holds the number of the disk it sits on
+            if let Some(disk) = zip_end_disk(&zip_path)?
+                && disk > last_index
+            {
+                return Err(missing(last_index + 1));
+            }
+            volumes.push(zip_path);
+        } else if volume.scheme == VolumeScheme::Numbered && !is_complete_split(&volume, &volumes)?
+        {
+            return Err(missing(last_index + 1));
+        } else if volume.scheme == VolumeScheme::RarParts
+            && let Some(last) = volumes.last()
+            && rar_continues(last)? == Some(true)
+        {
+            return Err(missing(last_index + 1));
+        }
+
+        Ok(Some(Self {
+            scheme: volume.scheme,
+            volumes,
+        }))
+    }
+
+    // The volume an archiver should be pointed at
+    pub fn entry_point(&self) -> &Path {
+        match self.scheme {
+            VolumeScheme::ZipSpanned => self.volumes.last(),
+            _ => self.volumes.first(),
+        }
+        .map(PathBuf::as_path)
+        .unwrap_or(Path::new(""))
+    }
+}
+
+// Returns the file to open for an archive path, validating volume sets along the way
+pub fn resolve_volume_path(path: &Path) -> Result<PathBuf> {
+    Ok(match VolumeSet::detect(path)? {
+        Some(set) => set.entry_point().to_owned(),
+        None => path.to_owned(),
+    })
+}
+
+// Plain byte splits of 7z and zip archives can be checked against their own headers
+fn is_complete_split(volume: &VolumeName, volumes: &[PathBuf]) -> Result<bool> {
+    let inner = volume.base.to_ascii_lowercase();
+    let total: u64 = volumes
+        .iter()
+        .map(|path| fs::metadata(path).map(|meta| meta.len()))
+        .sum::<std::io::Result<u64>>()?;
+
+    if inner.ends_with(".7z") {
+        let mut header = [0u8; 32];
+        File::open(&volumes[0])?.read_exact(&mut header)?;
+        if header[..6] != [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C] {
+            return Ok(true);
+        }
+        let next_header_offset = u64::from_le_bytes(header[12..20].try_into()?);
+        let next_header_size = u64::from_le_bytes(header[20..28].try_into()?);
+        return Ok(total
+            >= next_header_offset
+                .saturating_add(next_header_size)
+                .saturating_add(32));
+    }
+
+    if inner.ends_with(".zip") {
+        let last = volumes.last().ok_or(anyhow!("Failed to get last volume"))?;
+        return Ok(zip_end_disk(last)?.is_some());
+    }
+
+    Ok(true)
+}
+
+const RAR4_SIGNATURE: [u8; 7] = *b"Rar!\x1A\x07\x00";
+const RAR5_SIGNATURE: [u8; 8] = *b"Rar!\x1A\x07\x01\x00";
+
+// Walks the block headers of a rar volume to its end-of-archive block, which tells whether another
+// volume follows. `None` when that block is missing or the headers are encrypted.
+fn rar_continues(path: &Path) -> Result<Option<bool>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let len = file.get_ref().metadata()?.len();
+    let mut signature = [0u8; 8];
+    if file.read_exact(&mut signature).is_err() {
+        return Ok(None);
+    }
+
+    if signature == RAR5_SIGNATURE {
+        let mut offset = 8;
+        while offset < len {
+            file.seek(SeekFrom::Start(offset + 4))?;
+            let (size, size_len) = read_vint(&mut file)?;
+            let kind = read_vint(&mut file)?.0;
+            let flags = read_vint(&mut file)?.0;
+            if flags & 0x0001 != 0 {
+                read_vint(&mut file)?;
+            }
+            let data_size = if flags & 0x0002 != 0 {
+                read_vint(&mut file)?.0
+            } else {
+                0
+            };
+            match kind {
+                // Archive encryption header, everything after it is unreadable without the password
+                4 => return Ok(None),
+                5 => return Ok(Some(read_vint(&mut file)?.0 & 0x0001 != 0)),
+                _ => {}
+            }
+            offset = (offset + 4 + size_len)
+                .saturating_add(size)
+                .saturating_add(data_size);
+        }
+        return Ok(None);
+    }
+
+    if signature[..7] == RAR4_SIGNATURE {
+        let mut offset = 7;
+        while offset + 7 <= len {
+            file.seek(SeekFrom::Start(offset))?;
+            let mut header = [0u8; 7];
+            file.read_exact(&mut header)?;
+            let kind = header[2];
+            let flags = u16::from_le_bytes([header[3], header[4]]);
+            let size = u64::from(u16::from_le_bytes([header[5], header[6]]));
+            if size < 7 {
+                return Ok(None);
+            }
+            match kind {
+                // Main header of an archive with encrypted headers
+                0x73 if flags & 0x0080 != 0 => return Ok(None),
+                0x7B => return Ok(Some(flags & 0x0001 != 0)),
+                _ => {}
+            }
+
+            let mut data_size = 0;
+            if flags & 0x8000 != 0 || kind == 0x74 {
+                let mut low = [0u8; 4];
+                file.read_exact(&mut low)?;
+                data_size = u64::from(u32::from_le_bytes(low));
+                // File headers keep the high half of large sizes further in
+                if kind == 0x74 && flags & 0x0100 != 0 {
+                    file.seek(SeekFrom::Start(offset + 32))?;
+                    let mut high = [0u8; 4];
+                    file.read_exact(&mut high)?;
+                    data_size |= u64::from(u32::from_le_bytes(high)) << 32;
+                }
+            }
+            offset = (offset + size).saturating_add(data_size);
+        }
+    }
+
+    Ok(None)
+}
+
+// Variable length integer of rar5 headers, returned with the number of bytes it took
+fn read_vint(reader: &mut impl Read) -> Result<(u64, u64)> {
+    let mut value = 0;
+    for index in 0..10 {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        value |= u64::from(byte[0] & 0x7F) << (index * 7);
+        if byte[0] & 0x80 == 0 {
+            return Ok((value, index + 1));
+        }
+    }
+    Err(anyhow!("Malformed rar header"))
+}
+
+fn zip_end_disk(path: &Path) -> Result<Option<u32>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let tail_len = len.min(22 + u64::from(u16::MAX));
+    file.seek(SeekFrom::Start(len - tail_len))?;
+    let mut tail = Vec::with_capacity(tail_len as usize);
+    file.read_to_end(&mut tail)?;
+
+    Ok(tail
+        .windows(4)
+        .rposition(|window| window == [0x50, 0x4B, 0x05, 0x06])
+        .and_then(|pos| tail.get(pos + 4..pos + 6))
+        .map(|disk| u32::from(u16::from_le_bytes([disk[0], disk[1]]))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signature, main header and end-of-archive block of a rar5 volume, checksums left empty
+    fn rar5_volume(continues: bool) -> Vec<u8> {
+        let mut data = RAR5_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0, 3, 1, 0, 1]);
+        data.extend_from_slice(&[0, 0, 0, 0, 3, 5, 0, u8::from(continues)]);
+        data
+    }
+
+    fn rar4_volume(continues: bool) -> Vec<u8> {
+        let mut data = RAR4_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0, 0, 0x73, 0x01, 0, 13, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(&[0, 0, 0x7B, u8::from(continues), 0, 7, 0]);
+        data
+    }
+
+    #[test]
+    fn checks_rar_parts_against_the_last_volume() {
+        for volume in [rar5_volume, rar4_volume] {
+            let dir = tempfile::tempdir().unwrap();
+            let first = dir.path().join("app.part1.rar");
+            fs::write(&first, volume(true)).unwrap();
+            fs::write(dir.path().join("app.part2.rar"), volume(true)).unwrap();
+            assert_eq!(
+                VolumeSet::detect(&first).unwrap_err().to_string(),
+                "Missing archive volume: app.part3.rar"
+            );
+
+            fs::write(dir.path().join("app.part2.rar"), volume(false)).unwrap();
+            let set = VolumeSet::detect(&first).unwrap().unwrap();
+            assert_eq!(set.scheme, VolumeScheme::RarParts);
+            assert_eq!(set.volumes.len(), 2);
+        }
+    }
+
+    #[test]
+    fn needs_a_second_numbered_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("backup.bin.001");
+        fs::write(&first, b"data").unwrap();
+        assert!(!VolumeSet::is_volume_path(&first));
+        assert!(VolumeSet::detect(&first).unwrap().is_none());
+
+        fs::write(dir.path().join("backup.bin.002"), b"data").unwrap();
+        let set = VolumeSet::detect(&first).unwrap().unwrap();
+        assert_eq!(set.scheme, VolumeScheme::Numbered);
+        assert_eq!(set.volumes.len(), 2);
+
+        fs::remove_file(&first).unwrap();
+        assert!(VolumeSet::is_volume_path(
+            &dir.path().join("backup.bin.002")
+        ));
+    }
+}
@@ -0,0 +1,315 @@
+use super::{
+    ArchiveEntry, ExtractionCache, UnwrappedArchive, join_entry_path, open_payload, sanitize_path,
+    saved_password,
+};
+use crate::utils::pe::{PeFile, PeSubsystem};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ts_rs::TS;
+
+// Executables with these in their name are rarely what the user wants to launch
+const HELPER_KEYWORDS: [&str; 14] = [
+    "unins", "update", "upgrade", "crash", "report", "helper", "setup", "install", "redist",
+    "elevate", "service", "daemon", "debug", "notif",
+];
+// Name tokens that describe a build rather than the product
+const NOISE_TOKENS: [&str; 12] = [
+    "x64", "x86", "win", "win32", "win64", "windows", "amd64", "arm64", "portable", "release",
+    "final", "bin",
+];
+// Only the best candidates are extracted to have their PE headers read
+const INSPECTED_CANDIDATES: usize = 5;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionSignal {
+    NameMatchesArchive,
+    NameMatchesFolder,
+    HelperName,
+    Depth,
+    Size,
+    GuiSubsystem,
+    ConsoleSubsystem,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct SuggestionReason {
+    pub signal: SuggestionSignal,
+    pub points: i32,
+    pub detail: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ExecutableSuggestion {
+    pub path: String,
+    pub score: i32,
+    pub reasons: Vec<SuggestionReason>,
+}
+
+impl ExecutableSuggestion {
+    fn push(&mut self, signal: SuggestionSignal, points: i32, detail: String) {
+        if points == 0 {
+            return;
+        }
+        self.score += points;
+        self.reasons.push(SuggestionReason {
+            signal,
+            points,
+            detail,
+        });
+    }
+}
+
+// A ranked executable that was extracted to read its PE headers
+pub struct InspectedExecutable {
+    pub path: String,
+    pub pe: PeFile,
+}
+
+// Ranks every `.exe` in an archive by how likely it is to be the main program, best first
+pub async fn suggest_executables(
+    path: &str,
+    password: Option<&str>,
+) -> Result<Vec<ExecutableSuggestion>> {
+    let password = match password {
+        Some(password) => password.to_owned(),
+        None => saved_password(path).await?,
+    };
+    let payload = open_payload(path, Some(&password)).await?;
+    Ok(rank_executables(path, &payload, &password).await?.0)
+}
+
+pub async fn rank_executables(
+    path: &str,
+    payload: &UnwrappedArchive,
+    password: &str,
+) -> Result<(Vec<ExecutableSuggestion>, Vec<InspectedExecutable>)> {
+    let candidates: Vec<&ArchiveEntry> = payload
+        .entries
+        .iter()
+        .filter(|entry| {
+            !entry.is_dir
+                && entry.link_target.is_none()
+                && entry.name().to_lowercase().ends_with(".exe")
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let archive_name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let archive_tokens = name_tokens(strip_archive_extensions(&archive_name));
+    let min_depth = candidates
+        .iter()
+        .map(|entry| entry_depth(entry))
+        .min()
+        .unwrap_or_default();
+    let max_size = candidates
+        .iter()
+        .map(|entry| entry.size)
+        .max()
+        .unwrap_or_default();
+
+    let mut suggestions: Vec<ExecutableSuggestion> = candidates
+        .iter()
+        .map(|entry| score_entry(entry, &archive_tokens, min_depth, max_size))
+        .collect();
+    sort_suggestions(&mut suggestions);
+
+    // Reading PE headers needs the file itself, so only the leaders get extracted, in one pass
+    let leaders: Vec<String> = suggestions
+        .iter()
+        .take(INSPECTED_CANDIDATES)
+        .map(|suggestion| suggestion.path.clone())
+        .collect();
+    // Entries that fail to extract are simply left uninspected
+    let cached = ExtractionCache::new()
+        .extract_entries(path, payload, &leaders, Some(password))
+        .await
+        .ok();
+    let mut inspected = Vec::new();
+    for suggestion in suggestions.iter_mut().take(INSPECTED_CANDIDATES) {
+        let Some(cached) = &cached else {
+            break;
+        };
+        let extracted = join_entry_path(&cached.dir, &sanitize_path(&suggestion.path));
+        let Ok(pe) = PeFile::open(&extracted).await else {
+            continue;
+        };
+        match pe.subsystem {
+            PeSubsystem::Gui => suggestion.push(
+                SuggestionSignal::GuiSubsystem,
+                15,
+                "Windows GUI application".to_owned(),
+            ),
+            PeSubsystem::Console => suggestion.push(
+                SuggestionSignal::ConsoleSubsystem,
+                -15,
+                "Console application".to_owned(),
+            ),
+            _ => {}
+        }
+        inspected.push(InspectedExecutable {
+            path: suggestion.path.clone(),
+            pe,
+        });
+    }
+    sort_suggestions(&mut suggestions);
+
+    Ok((suggestions, inspected))
+}
+
+fn score_entry(
+    entry: &ArchiveEntry,
+    archive_tokens: &[String],
+    min_depth: usize,
+    max_size: u64,
+) -> ExecutableSuggestion {
+    let mut suggestion = ExecutableSuggestion {
+        path: entry.path.clone(),
+        score: 0,
+        reasons: Vec::new(),
+    };
+    let name = entry.name();
+    let stem = name.get(..name.len() - 4).unwrap_or(name);
+    let tokens = name_tokens(stem);
+
+    let archive_similarity = similarity(&tokens, archive_tokens);
+    suggestion.push(
+        SuggestionSignal::NameMatchesArchive,
+        (archive_similarity * 40.0).round() as i32,
+        format!(
+            "Name is {:.0}% similar to the archive name",
+            archive_similarity * 100.0
+        ),
+    );
+
+    let folder = entry
+        .path
+        .split('\\')
+        .filter(|part| !part.is_empty())
+        .rev()
+        .nth(1);
+    if let Some(folder) = folder {
+        let folder_similarity = similarity(&tokens, &name_tokens(folder));
+        suggestion.push(
+            SuggestionSignal::NameMatchesFolder,
+            (folder_similarity * 20.0).round() as i32,
+            format!(
+                "Name is {:.0}% similar to its folder '{folder}'",
+                folder_similarity * 100.0
+            ),
+        );
+    }
+
+    let lower_stem = stem.to_lowercase();
+    let archive_joined = archive_tokens.concat();
+    if let Some(keyword) = HELPER_KEYWORDS
+        .iter()
+        .find(|keyword| lower_stem.contains(*keyword) && !archive_joined.contains(*keyword))
+    {
+        suggestion.push(
+            SuggestionSignal::HelperName,
+            -50,
+            format!("Looks like a helper program ('{keyword}')"),
+        );
+    }
+
+    let extra_depth = entry_depth(entry) - min_depth;
+    suggestion.push(
+        SuggestionSignal::Depth,
+        -10 * extra_depth as i32,
+        format!("{extra_depth} level(s) deeper than the shallowest executable"),
+    );
+
+    if max_size > 0 {
+        let points = (entry.size as f64 / max_size as f64 * 15.0).round() as i32;
+        suggestion.push(
+            SuggestionSignal::Size,
+            points,
+            format!("{} bytes, the largest executable is {max_size}", entry.size),
+        );
+    }
+
+    suggestion
+}
+
+fn sort_suggestions(suggestions: &mut [ExecutableSuggestion]) {
+    suggestions.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+}
+
+fn entry_depth(entry: &ArchiveEntry) -> usize {
+    entry
+        .path
+        .split('\\')
+        .filter(|part| !part.is_empty())
+        .count()
+        .saturating_sub(1)
+}
+
+// `MyApp-1.2.0-win64.7z.001` -> `MyApp-1.2.0-win64`
+fn strip_archive_extensions(name: &str) -> &str {
+    let mut stem = name;
+    while let Some((rest, ext)) = stem.rsplit_once('.') {
+        let is_archive_ext = ext.len() <= 4
+            && (ext.bytes().all(|b| b.is_ascii_digit())
+                || ["zip", "7z", "rar", "tar", "gz", "tgz", "xz", "bz2", "exe"]
+                    .contains(&ext.to_ascii_lowercase().as_str()));
+        if rest.is_empty() || !is_archive_ext {
+            break;
+        }
+        stem = rest;
+    }
+    stem
+}
+
+// Lowercase words with version numbers and build markers dropped, camel case split apart
+fn name_tokens(name: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        let boundary = !c.is_alphanumeric() || (c.is_uppercase() && previous_lower);
+        if boundary && !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if c.is_alphanumeric() {
+            current.extend(c.to_lowercase());
+        }
+        previous_lower = c.is_lowercase();
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens.retain(|token| {
+        let is_version = token
+            .trim_start_matches('v')
+            .bytes()
+            .all(|b| b.is_ascii_digit());
+        !is_version && !NOISE_TOKENS.contains(&token.as_str())
+    });
+    tokens
+}
+
+fn similarity(a: &[String], b: &[String]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let (a_joined, b_joined) = (a.concat(), b.concat());
+    if a_joined == b_joined {
+        return 1.0;
+    }
+    if a_joined.contains(&b_joined) || b_joined.contains(&a_joined) {
+        return 0.7;
+    }
+    let shared = a.iter().filter(|token| b.contains(token)).count();
+    shared as f64 / a.len().max(b.len()) as f64 * 0.7
+}
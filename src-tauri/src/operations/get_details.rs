@@ -1,13 +1,12 @@
-use super::get_7z_path;
-use super::sanitize_path;
+use super::{ExtractionCache, join_entry_path, open_payload, sanitize_path};
+use crate::utils::pe::{
+    ExecutionLevel, PeFile, PeMachine, PeSubsystem, RuntimeDependency, SignatureInfo,
+};
 use anyhow::{Result, anyhow};
 use base64::{Engine, engine::general_purpose::STANDARD};
+use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use std::path::Path;
-use systemicons::get_icon;
-use tempfile::tempdir;
-use tokio::process::Command;
 use ts_rs::TS;
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
@@ -25,159 +24,172 @@ pub struct ExeDetails {
     pub version: String,
     pub copyright: String,
     pub icon_data_url: String,
+    pub machine: PeMachine,
+    pub subsystem: PeSubsystem,
+    // Managed .NET assembly
+    pub is_dotnet: bool,
+    // `requestedExecutionLevel` of the embedded manifest
+    pub execution_level: Option<ExecutionLevel>,
+    pub runtime_dependencies: Vec<RuntimeDependency>,
+    // Whether an Authenticode blob is attached, not whether it verifies
+    pub has_signature: bool,
+    // Parsed but not cryptographically verified, see `SignatureInfo`
+    pub unverified_signature: Option<SignatureInfo>,
 }
 
-pub async fn get_details(input: ExePath) -> Result<ExeDetails> {
-    let temp_dir = tempdir()?;
-
-    let sanitized_path = sanitize_path(&input.executable_path);
-    let temp_exe_path = temp_dir.path().join(&sanitized_path);
-
-    if let Some(parent) = temp_exe_path.parent() {
-        tokio::fs::create_dir_all(parent).await?;
-    };
-
-    let temp_dir_canonical = std::fs::canonicalize(temp_dir.path())?;
-    let parent_canonical = if let Some(parent) = temp_exe_path.parent() {
-        std::fs::canonicalize(parent).ok()
-    } else {
-        Some(temp_dir_canonical.clone())
-    };
-
-    if let Some(parent_path) = parent_canonical {
-        if !parent_path.starts_with(&temp_dir_canonical) {
-            return Err(anyhow!(
-                "Security violation: Path traversal detected in executable path: {}",
-                input.executable_path
-            ));
-        }
-    }
-
-    let dir = format!(
-        "-o{}",
-        temp_dir
-            .path()
-            .to_str()
-            .ok_or(anyhow!("Failed to convert temp dir to str"))?
-    );
+// Outcome for one executable of a batch, failures do not abort the others
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct ExeDetailsResult {
+    pub executable_path: String,
+    pub details: Option<ExeDetails>,
+    pub error: Option<String>,
+}
 
-    let mut args = vec![
-        "e",
+pub async fn get_details(input: ExePath) -> Result<ExeDetails> {
+    let (_, details) = extract_details(
         &input.zip_path,
-        &input.executable_path,
-        &dir,
-        "-y",
-        "-aoa",
-        "-snl",
-    ];
+        std::slice::from_ref(&input.executable_path),
+        input.password.as_deref(),
+    )
+    .await?
+    .pop()
+    .ok_or(anyhow!("No details for '{}'", input.executable_path))?;
+    details
+}
 
-    let mut pw = String::new();
-    if let Some(password) = &input.password {
-        pw = format!("-p{password}");
-    }
-    args.push(&pw);
+// Extracts every requested executable in one archive pass, then reads them in parallel
+pub async fn get_details_batch(
+    zip_path: &str,
+    executable_paths: Vec<String>,
+    password: Option<&str>,
+) -> Result<Vec<ExeDetailsResult>> {
+    Ok(extract_details(zip_path, &executable_paths, password)
+        .await?
+        .into_iter()
+        .map(|(executable_path, details)| match details {
+            Ok(details) => ExeDetailsResult {
+                executable_path,
+                details: Some(details),
+                error: None,
+            },
+            Err(e) => ExeDetailsResult {
+                executable_path,
+                details: None,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect())
+}
 
-    let output1 = Command::new(get_7z_path().await?)
-        .args(args)
-        .creation_flags(0x08000000)
-        .output()
+async fn extract_details(
+    zip_path: &str,
+    executable_paths: &[String],
+    password: Option<&str>,
+) -> Result<Vec<(String, Result<ExeDetails>)>> {
+    let payload = open_payload(zip_path, password).await?;
+    let cached = ExtractionCache::new()
+        .extract_entries(zip_path, &payload, executable_paths, password)
         .await?;
-    if !output1.status.success() {
-        let error_str = String::from_utf8_lossy(&output1.stderr).to_string();
-        if error_str.contains("Cannot open encrypted archive. Wrong password?") {
-            return Err(anyhow!("Wrong password"));
+    let cache_dir_canonical = std::fs::canonicalize(&cached.dir)?;
+
+    let tasks = executable_paths.iter().map(|executable_path| {
+        let extracted_file = join_entry_path(&cached.dir, &sanitize_path(executable_path));
+        let cache_dir_canonical = cache_dir_canonical.clone();
+        let executable_path = executable_path.clone();
+        async move {
+            tokio::task::spawn_blocking(move || {
+                if !extracted_file.exists() {
+                    return Err(anyhow!(
+                        "Failed to find executable '{}' in archive",
+                        executable_path
+                    ));
+                }
+                if !std::fs::canonicalize(&extracted_file)?.starts_with(&cache_dir_canonical) {
+                    return Err(anyhow!(
+                        "Security violation: Path traversal detected in executable path: {}",
+                        executable_path
+                    ));
+                }
+                read_details(&extracted_file)
+            })
+            .await?
         }
-        return Err(anyhow!("{}", String::from_utf8_lossy(&output1.stderr)));
-    }
-
-    let file_name = Path::new(&sanitized_path)
-        .file_name()
-        .ok_or(anyhow!("Failed to get file name"))?;
-
-    let extracted_file = temp_dir.path().join(file_name);
-
-    if !extracted_file.exists() {
-        return Err(anyhow!(
-            "Failed to find executable '{}' in archive",
-            input.executable_path
-        ));
-    }
+    });
+    let results = join_all(tasks).await;
 
-    let raw_icon = get_icon(&extracted_file.to_string_lossy(), 64)
-        .map_err(|e| anyhow!("Failed to extract icon from extracted file: {:?}", e))?;
-    let icon_data_url = format!("data:image/png;base64,{}", STANDARD.encode(&raw_icon));
+    drop(cached);
 
-    let extracted_file_str = extracted_file.to_string_lossy();
-    let safe_path_for_ps = extracted_file_str.replace("'", "''");
-    let ps_command = format!(
-        r#"[Console]::OutputEncoding = [System.Text.Encoding]::UTF8;
-        $ErrorActionPreference = 'Stop';
-        try {{
-            $file_path = '{safe_path_for_ps}';
-            $version_info = (Get-Item $file_path).VersionInfo;
-            Write-Output (ConvertTo-Json -Compress @{{
-                product_name = $version_info.ProductName;
-                file_description = $version_info.FileDescription;
-                original_filename = $version_info.OriginalFilename;
-                product_version = $version_info.ProductVersion;
-                file_version = $version_info.FileVersion;
-                copyright = $version_info.LegalCopyright;
-                filename = [System.IO.Path]::GetFileNameWithoutExtension($file_path);
-            }})
-        }} catch {{
-            Write-Output (ConvertTo-Json -Compress @{{
-                error = $_.Exception.Message;
-            }})
-        }}"#
-    );
-
-    let output2 = Command::new("powershell")
-        .args([
-            "-NoProfile",
-            "-NonInteractive",
-            "-ExecutionPolicy",
-            "Bypass",
-            "-Command",
-            &ps_command,
-        ])
-        .creation_flags(0x08000000)
-        .output()
-        .await?;
+    Ok(executable_paths.iter().cloned().zip(results).collect())
+}
 
-    if !output2.status.success() {
-        return Err(anyhow!("{}", String::from_utf8_lossy(&output2.stderr)));
-    }
+fn read_details(extracted_file: &Path) -> Result<ExeDetails> {
+    let file_stem = extracted_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // Scripts, jars and other non-PE launchers only have their file name to go by
+    let Ok(pe) = PeFile::parse(std::fs::read(extracted_file)?) else {
+        return Ok(ExeDetails {
+            product_name: file_stem,
+            version: String::new(),
+            copyright: String::new(),
+            icon_data_url: String::new(),
+            machine: PeMachine::Other,
+            subsystem: PeSubsystem::Other,
+            is_dotnet: false,
+            execution_level: None,
+            runtime_dependencies: Vec::new(),
+            has_signature: false,
+            unverified_signature: None,
+        });
+    };
 
-    let output_str = String::from_utf8_lossy(&output2.stdout);
-    let details: Value = serde_json::from_str(&output_str)?;
+    // Every resource is read best-effort, a malformed one only costs its own field
+    let icon_data_url = pe
+        .main_icon()
+        .ok()
+        .flatten()
+        .and_then(|icon| icon.frame_for(64).and_then(|frame| frame.to_png().ok()))
+        .map(|png| format!("data:image/png;base64,{}", STANDARD.encode(png)))
+        .unwrap_or_default();
+    let version_info = pe.version_info().ok().flatten().unwrap_or_default();
 
-    let product_name = get_valid_str(&details["product_name"])
-        .or_else(|| get_valid_str(&details["file_description"]))
+    let product_name = version_info
+        .string("ProductName")
+        .or_else(|| version_info.string("FileDescription"))
+        .map(str::to_owned)
         .or_else(|| {
-            get_valid_str(&details["original_filename"]).map(|s| s.trim_end_matches(".exe"))
+            version_info
+                .string("OriginalFilename")
+                .map(|name| name.trim_end_matches(".exe").to_owned())
         })
-        .or_else(|| get_valid_str(&details["filename"]))
-        .unwrap_or_default();
-
-    let version = get_valid_str(&details["product_version"])
-        .or_else(|| get_valid_str(&details["file_version"]))
+        .unwrap_or(file_stem);
+
+    let version = version_info
+        .string("ProductVersion")
+        .or_else(|| version_info.string("FileVersion"))
+        .map(str::to_owned)
+        .or(version_info.product_version.clone())
+        .or(version_info.file_version.clone())
         .unwrap_or_default();
 
-    let copyright = get_valid_str(&details["copyright"]).unwrap_or_default();
-
-    drop(temp_dir);
+    let copyright = version_info
+        .string("LegalCopyright")
+        .unwrap_or_default()
+        .to_owned();
 
     Ok(ExeDetails {
-        product_name: product_name.to_owned(),
-        version: version.to_owned(),
-        copyright: copyright.to_owned(),
+        product_name,
+        version,
+        copyright,
         icon_data_url,
+        machine: pe.machine,
+        subsystem: pe.subsystem,
+        is_dotnet: pe.has_clr(),
+        execution_level: pe.execution_level().ok().flatten(),
+        runtime_dependencies: pe.runtime_dependencies().unwrap_or_default(),
+        has_signature: pe.certificate_table().is_some(),
+        unverified_signature: pe.signature().ok().flatten(),
     })
 }
-
-fn get_valid_str(value: &Value) -> Option<&str> {
-    value
-        .as_str()
-        .filter(|s| !s.trim().is_empty())
-        .map(|s| s.trim())
-}
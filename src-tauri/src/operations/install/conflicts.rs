@@ -0,0 +1,261 @@
+use super::{AppInstallConfig, ToolInstallConfig};
+use crate::configs::library::Library;
+use crate::operations::get_archive_content;
+use crate::utils::path::path_entries;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ts_rs::TS;
+
+// Extensions Windows runs without being typed, as in the default PATHEXT
+const EXECUTABLE_EXTENSIONS: [&str; 4] = ["exe", "com", "bat", "cmd"];
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    // Another installed app shares the name, and with it the uninstall key and the shortcut names
+    DuplicateName,
+    // The install directory already holds files, installing replaces them
+    TargetNotEmpty,
+    // The PATH entry exists already, uninstalling would remove it for whoever added it first
+    PathEntryPresent,
+    // An executable of the PATH directory has the same command name as one already on PATH
+    ShadowedExecutable,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    Rename,
+    ChoosePath,
+    Overwrite,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct InstallConflict {
+    pub kind: ConflictKind,
+    // The name, directory or executable that collides
+    pub subject: String,
+    pub detail: String,
+    pub resolution: ConflictResolution,
+}
+
+pub enum InstallRequest<'a> {
+    App(&'a AppInstallConfig),
+    Tool(&'a ToolInstallConfig),
+}
+
+// Looks for everything an install would collide with, without changing anything
+pub async fn check_install_conflicts(request: InstallRequest<'_>) -> Result<Vec<InstallConflict>> {
+    let mut conflicts = Vec::new();
+    match request {
+        InstallRequest::App(config) => {
+            let details = &config.app.details;
+            let library = Library::load().await?;
+            for other in library.apps.iter().filter(|other| {
+                other.installed
+                    && other.id != config.app.id
+                    && other
+                        .details
+                        .info
+                        .name
+                        .eq_ignore_ascii_case(&details.info.name)
+            }) {
+                conflicts.push(InstallConflict {
+                    kind: ConflictKind::DuplicateName,
+                    subject: details.info.name.clone(),
+                    detail: format!(
+                        "{} is already installed at {}",
+                        other.details.info.name, other.details.install_path
+                    ),
+                    resolution: ConflictResolution::Rename,
+                });
+            }
+
+            conflicts.extend(check_target(&details.install_path).await?);
+
+            if details.config.add_to_path.0 {
+                let present = match config.path_directory()? {
+                    Some(directory) => find_path_entry(details.current_user_only, |entry| {
+                        same_directory(entry, &directory)
+                    })?,
+                    // Any entry into the install directory may turn out to be the one added
+                    None => find_path_entry(details.current_user_only, |entry| {
+                        is_within(entry, &details.install_path)
+                    })?,
+                };
+                conflicts.extend(present);
+            }
+        }
+        InstallRequest::Tool(config) => {
+            let details = &config.tool.details;
+            conflicts.extend(check_target(&details.install_path).await?);
+
+            if details.add_to_path.0 {
+                let directory = config.path_directory()?;
+                conflicts.extend(find_path_entry(true, |entry| {
+                    same_directory(entry, &directory)
+                })?);
+                conflicts.extend(check_shadowed(config, &directory).await?);
+            }
+        }
+    }
+    Ok(conflicts)
+}
+
+// Fails with every conflict whose kind was not explicitly overridden
+pub fn ensure_resolved(conflicts: &[InstallConflict], overrides: &[ConflictKind]) -> Result<()> {
+    let unresolved: Vec<String> = conflicts
+        .iter()
+        .filter(|conflict| !overrides.contains(&conflict.kind))
+        .map(|conflict| conflict.detail.clone())
+        .collect();
+    if !unresolved.is_empty() {
+        return Err(anyhow!("Install conflicts: {}", unresolved.join("; ")));
+    }
+    Ok(())
+}
+
+async fn check_target(install_path: &str) -> Result<Option<InstallConflict>> {
+    let Ok(mut entries) = tokio::fs::read_dir(install_path).await else {
+        return Ok(None);
+    };
+    if entries.next_entry().await?.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(InstallConflict {
+        kind: ConflictKind::TargetNotEmpty,
+        subject: install_path.to_owned(),
+        detail: format!("{install_path} is not empty"),
+        resolution: ConflictResolution::ChoosePath,
+    }))
+}
+
+fn find_path_entry(
+    current_user_only: bool,
+    matches: impl Fn(&str) -> bool,
+) -> Result<Option<InstallConflict>> {
+    Ok(path_entries(current_user_only)?
+        .into_iter()
+        .find(|entry| matches(entry))
+        .map(|entry| InstallConflict {
+            kind: ConflictKind::PathEntryPresent,
+            detail: format!("{entry} is already on PATH"),
+            subject: entry,
+            resolution: ConflictResolution::Overwrite,
+        }))
+}
+
+// Compares the executables that will sit in the PATH directory with the PATH of this process
+async fn check_shadowed(
+    config: &ToolInstallConfig,
+    directory: &str,
+) -> Result<Vec<InstallConflict>> {
+    let install_path = config.tool.details.install_path.clone();
+    let Some(relative) = relative_to(directory, &install_path) else {
+        return Ok(Vec::new());
+    };
+
+    let filter = config.filter.compile()?;
+    let mut targets: Vec<String> =
+        get_archive_content(&config.zip_path, Some(&config.tool.archive_password))
+            .await?
+            .into_iter()
+            .filter(|entry| !entry.is_dir)
+            .filter_map(|entry| filter.target(&entry.path))
+            .collect();
+    strip_wrapper_folders(&mut targets);
+
+    let names: Vec<String> = targets
+        .iter()
+        .filter_map(|target| {
+            let (parent, name) = target.rsplit_once('\\').unwrap_or(("", target));
+            (parent.eq_ignore_ascii_case(&relative) && executable_stem(name).is_some())
+                .then(|| name.to_owned())
+        })
+        .collect();
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let path = std::env::var("PATH").unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let directories: Vec<&str> = path
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty() && !is_within(entry, &install_path))
+            .collect();
+        let mut conflicts = Vec::new();
+        for name in names {
+            let Some(stem) = executable_stem(&name) else {
+                continue;
+            };
+            let existing = directories.iter().find_map(|directory| {
+                EXECUTABLE_EXTENSIONS
+                    .iter()
+                    .map(|extension| Path::new(directory).join(format!("{stem}.{extension}")))
+                    .find(|candidate| candidate.is_file())
+            });
+            if let Some(existing) = existing {
+                conflicts.push(InstallConflict {
+                    kind: ConflictKind::ShadowedExecutable,
+                    detail: format!("{name} has the same command name as {}", existing.display()),
+                    subject: name,
+                    resolution: ConflictResolution::ChoosePath,
+                });
+            }
+        }
+        Ok(conflicts)
+    })
+    .await?
+}
+
+// Mirrors `flatten_nested_folders`, which lifts the contents of a lone top folder up to three times
+fn strip_wrapper_folders(targets: &mut [String]) {
+    for _ in 0..3 {
+        let Some((folder, _)) = targets.first().and_then(|target| target.split_once('\\')) else {
+            return;
+        };
+        let prefix = format!("{folder}\\");
+        if !targets.iter().all(|target| target.starts_with(&prefix)) {
+            return;
+        }
+        for target in targets.iter_mut() {
+            target.drain(..prefix.len());
+        }
+    }
+}
+
+fn executable_stem(name: &str) -> Option<&str> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    EXECUTABLE_EXTENSIONS
+        .iter()
+        .any(|known| extension.eq_ignore_ascii_case(known))
+        .then_some(stem)
+}
+
+fn normalize(directory: &str) -> String {
+    directory.trim().trim_end_matches('\\').to_lowercase()
+}
+
+fn same_directory(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+fn is_within(path: &str, directory: &str) -> bool {
+    relative_to(path, directory).is_some()
+}
+
+// `path` below `directory`, empty when they are the same
+fn relative_to(path: &str, directory: &str) -> Option<String> {
+    let path = normalize(path);
+    let directory = normalize(directory);
+    if path == directory {
+        return Some(String::new());
+    }
+    path.strip_prefix(&format!("{directory}\\"))
+        .map(str::to_owned)
+}
@@ -1,11 +1,21 @@
 use crate::configs::library::*;
-use crate::operations::extract_archive_files;
-use crate::operations::install::flatten_nested_folders;
+use crate::operations::install::{
+    ConflictKind, InstallJournal, InstallRequest, SideEffect, check_install_conflicts,
+    ensure_resolved, flatten_nested_folders, unstaged_path,
+};
+use crate::operations::{
+    ExtractTask, ExtractionFilter, FileInventory, ProgressReporter, ensure_archive_intact,
+    extract_archive_files, suggest_executables,
+};
 use crate::utils::convert_base64_to_ico;
 use crate::utils::path::add_to_path;
+use crate::utils::pe::PeFile;
 use crate::utils::registry::create_registry_entries;
-use crate::utils::shortcuts::{create_desktop_shortcut, create_start_menu_shortcut};
-use anyhow::Result;
+use crate::utils::shortcuts::{
+    create_desktop_shortcut, create_start_menu_shortcut, desktop_shortcut_path,
+    start_menu_shortcut_path,
+};
+use anyhow::{Result, anyhow};
 use mslnk::ShellLink;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -19,29 +29,117 @@ pub struct AppInstallConfig {
     pub archive_exe_path: String,
     pub archive_path_dir: String,
     pub zip_path: String,
+    #[serde(default)]
+    pub filter: ExtractionFilter,
+    // Conflicts the user accepted, see `check_install_conflicts`
+    #[serde(default)]
+    pub overrides: Vec<ConflictKind>,
+}
+
+impl AppInstallConfig {
+    // Where the PATH entry points, `None` for the folder of the executable which is only known after extraction
+    pub fn path_directory(&self) -> Result<Option<String>> {
+        let path_dir = if self.archive_path_dir.is_empty() {
+            String::new()
+        } else {
+            self.filter
+                .relative_path(&self.archive_path_dir)
+                .ok_or(anyhow!("PATH directory is outside the extraction subtree"))?
+        };
+        let configured = &self.app.details.config.add_to_path.1;
+        Ok(if !path_dir.is_empty() {
+            Some(format!("{}\\{}", self.app.details.install_path, path_dir))
+        } else if configured.is_empty() {
+            None
+        } else {
+            Some(configured.clone())
+        })
+    }
 }
 
 pub async fn install_app(config: AppInstallConfig, app: &AppHandle) -> Result<(String, String)> {
     let mut config = config;
     Library::init_app(&mut config.app).await?;
 
+    // Installs started without a chosen executable take the best ranked one
+    if config.archive_exe_path.is_empty() {
+        config.archive_exe_path =
+            suggest_executables(&config.zip_path, Some(&config.app.archive_password))
+                .await?
+                .into_iter()
+                .next()
+                .ok_or(anyhow!("No executable found in archive"))?
+                .path;
+    }
+
+    if config
+        .filter
+        .compile()?
+        .target(&config.archive_exe_path)
+        .is_none()
+    {
+        return Err(anyhow!("Executable is excluded by the extraction filter"));
+    }
+    config.app.details.filter = config.filter.clone();
+    let path_directory = config.path_directory()?;
+    ensure_resolved(
+        &check_install_conflicts(InstallRequest::App(&config)).await?,
+        &config.overrides,
+    )?;
+
     app.emit("app_install_progress", 0)?;
 
-    tokio::fs::create_dir_all(&config.app.details.install_path).await?;
+    ensure_archive_intact(&config.zip_path, &config.app.archive_password).await?;
+
+    let mut journal = InstallJournal::begin(&config.app.id).await?;
+    if let Err(e) = install_app_files(&mut config, path_directory, app, &mut journal).await {
+        if let Err(rollback_error) = journal.rollback().await {
+            eprintln!("{rollback_error}");
+        }
+        return Err(e);
+    }
+    journal.commit().await?;
+
+    // Only committed installs are listed, a crash before this leaves nothing behind
+    let mut app_list = Library::load().await?;
+    app_list.add_app(config.app.clone()).await?;
+
+    app.emit("app_install_progress", 101)?;
+
+    Ok((
+        config.app.details.install_path,
+        config.app.details.full_path,
+    ))
+}
+
+async fn install_app_files(
+    config: &mut AppInstallConfig,
+    path_directory: Option<String>,
+    app: &AppHandle,
+    journal: &mut InstallJournal,
+) -> Result<()> {
+    let staging = journal.stage(&config.app.details.install_path).await?;
     extract_archive_files(
         &config.zip_path,
-        &config.app.details.install_path,
-        Some(app),
+        &staging.to_string_lossy(),
+        &config.filter,
+        Some(ProgressReporter::for_app(app, ExtractTask::AppInstall)),
         &config.app.archive_password,
-        "app_install_progress",
     )
     .await?;
 
-    config.app.details.full_path = flatten_nested_folders(
-        &config.app.details.install_path,
-        Some(&config.archive_exe_path),
-    )
-    .await?;
+    let staged_exe_path =
+        flatten_nested_folders(&staging.to_string_lossy(), Some(&config.archive_exe_path)).await?;
+    config.app.details.unverified_signature = PeFile::open(Path::new(&staged_exe_path))
+        .await
+        .ok()
+        .and_then(|pe| pe.signature().ok().flatten());
+
+    journal
+        .swap_into_place(&staging, &config.app.details.install_path)
+        .await?;
+    config.app.details.full_path =
+        unstaged_path(&staged_exe_path, &staging, &config.app.details.install_path)?;
 
     let mut shell_link = ShellLink::new(&config.app.details.full_path)?;
     if config.app.details.config.custom_icon {
@@ -50,14 +148,31 @@ pub async fn install_app(config: AppInstallConfig, app: &AppHandle) -> Result<(S
             &format!("{}-{}", config.app.details.info.name, config.app.id),
         )
         .await?;
+        journal
+            .record(SideEffect::FileWritten {
+                path: icon_path.clone(),
+            })
+            .await?;
         shell_link.set_icon_location(Some(icon_path));
     }
 
     if config.app.details.config.create_desktop_shortcut {
+        journal
+            .record_shortcut(&desktop_shortcut_path(&config.app.details.info.name)?)
+            .await?;
         create_desktop_shortcut(&shell_link, &config.app.details.info.name)?;
     }
 
     if config.app.details.config.create_start_menu_shortcut {
+        journal
+            .record_shortcut(
+                &start_menu_shortcut_path(
+                    config.app.details.current_user_only,
+                    &config.app.details.info.name,
+                )
+                .await?,
+            )
+            .await?;
         create_start_menu_shortcut(
             &shell_link,
             config.app.details.current_user_only,
@@ -67,41 +182,42 @@ pub async fn install_app(config: AppInstallConfig, app: &AppHandle) -> Result<(S
     }
 
     if config.app.details.config.create_registry_key {
+        journal
+            .record_uninstall_key(
+                &config.app.details.info.name,
+                config.app.details.current_user_only,
+            )
+            .await?;
         create_registry_entries(&config.app)?;
     }
 
     if config.app.details.config.add_to_path.0 {
-        config.app.details.config.add_to_path.1 = if config.archive_path_dir.is_empty() {
-            if config.app.details.config.add_to_path.1.is_empty() {
-                Path::new(&config.app.details.full_path)
-                    .parent()
-                    .expect("Failed to get parent directory")
-                    .to_string_lossy()
-                    .to_string()
-            } else {
-                config.app.details.config.add_to_path.1
-            }
-        } else {
-            let normalized_path = config
-                .archive_path_dir
-                .trim_start_matches(['/', '\\'])
-                .replace("/", "\\");
-            format!("{}\\{}", config.app.details.install_path, normalized_path)
+        config.app.details.config.add_to_path.1 = match path_directory {
+            Some(directory) => directory,
+            None => Path::new(&config.app.details.full_path)
+                .parent()
+                .expect("Failed to get parent directory")
+                .to_string_lossy()
+                .to_string(),
         };
 
+        journal
+            .record_path_entry(
+                &config.app.details.config.add_to_path.1,
+                config.app.details.current_user_only,
+            )
+            .await?;
         add_to_path(
             &config.app.details.config.add_to_path.1,
             config.app.details.current_user_only,
         )?;
     }
 
-    let mut app_list = Library::load().await?;
-    app_list.add_app(config.app.clone()).await?;
-
-    app.emit("app_install_progress", 101)?;
+    let inventory = FileInventory::scan(&config.app.id, &config.app.details.install_path).await?;
+    journal
+        .record_file(&FileInventory::file_path(&config.app.id)?)
+        .await?;
+    inventory.save().await?;
 
-    Ok((
-        config.app.details.install_path,
-        config.app.details.full_path,
-    ))
+    Ok(())
 }
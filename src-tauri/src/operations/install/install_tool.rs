@@ -1,9 +1,13 @@
 use crate::{
     configs::library::*,
-    operations::{extract_archive_files, flatten_nested_folders},
+    operations::{
+        ConflictKind, ExtractTask, ExtractionFilter, FileInventory, InstallJournal, InstallRequest,
+        ProgressReporter, check_install_conflicts, ensure_archive_intact, ensure_resolved,
+        extract_archive_files, flatten_nested_folders,
+    },
     utils::path::add_to_path,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter};
 use ts_rs::TS;
@@ -14,44 +18,99 @@ pub struct ToolInstallConfig {
     pub tool: Tool,
     pub archive_path_dir: String,
     pub zip_path: String,
+    #[serde(default)]
+    pub filter: ExtractionFilter,
+    // Conflicts the user accepted, see `check_install_conflicts`
+    #[serde(default)]
+    pub overrides: Vec<ConflictKind>,
+}
+
+impl ToolInstallConfig {
+    // Where the PATH entry points once installed
+    pub fn path_directory(&self) -> Result<String> {
+        let path_dir = if self.archive_path_dir.is_empty() {
+            String::new()
+        } else {
+            self.filter
+                .relative_path(&self.archive_path_dir)
+                .ok_or(anyhow!("PATH directory is outside the extraction subtree"))?
+        };
+        Ok(if self.tool.details.add_to_path.1.is_empty() {
+            self.tool.details.install_path.clone()
+        } else {
+            format!("{}\\{}", self.tool.details.install_path, path_dir)
+        })
+    }
 }
 
 pub async fn install_tool(config: ToolInstallConfig, app: &AppHandle) -> Result<String> {
     let mut config = config;
     Library::init_tool(&mut config.tool).await?;
+    config.tool.details.filter = config.filter.clone();
+
+    let path_directory = config.path_directory()?;
+    ensure_resolved(
+        &check_install_conflicts(InstallRequest::Tool(&config)).await?,
+        &config.overrides,
+    )?;
 
     app.emit("tool_install_progress", 0)?;
 
-    tokio::fs::create_dir_all(&config.tool.details.install_path).await?;
+    ensure_archive_intact(&config.zip_path, &config.tool.archive_password).await?;
+
+    let mut journal = InstallJournal::begin(&config.tool.id).await?;
+    if let Err(e) = install_tool_files(&mut config, path_directory, app, &mut journal).await {
+        if let Err(rollback_error) = journal.rollback().await {
+            eprintln!("{rollback_error}");
+        }
+        return Err(e);
+    }
+    journal.commit().await?;
+
+    // Only committed installs are listed, a crash before this leaves nothing behind
+    let mut app_list = Library::load().await?;
+    app_list.add_tool(config.tool.clone()).await?;
+
+    app.emit("tool_install_progress", 101)?;
+
+    Ok(config.tool.details.install_path)
+}
+
+async fn install_tool_files(
+    config: &mut ToolInstallConfig,
+    path_directory: String,
+    app: &AppHandle,
+    journal: &mut InstallJournal,
+) -> Result<()> {
+    let staging = journal.stage(&config.tool.details.install_path).await?;
     extract_archive_files(
         &config.zip_path,
-        &config.tool.details.install_path,
-        Some(app),
+        &staging.to_string_lossy(),
+        &config.filter,
+        Some(ProgressReporter::for_app(app, ExtractTask::ToolInstall)),
         &config.tool.archive_password,
-        "tool_install_progress",
     )
     .await?;
 
-    flatten_nested_folders(&config.tool.details.install_path, None).await?;
+    flatten_nested_folders(&staging.to_string_lossy(), None).await?;
+    journal
+        .swap_into_place(&staging, &config.tool.details.install_path)
+        .await?;
 
     if config.tool.details.add_to_path.0 {
-        config.tool.details.add_to_path.1 = if config.tool.details.add_to_path.1.is_empty() {
-            config.tool.details.install_path.clone()
-        } else {
-            let normalized_path = config
-                .archive_path_dir
-                .trim_start_matches(['/', '\\'])
-                .replace("/", "\\");
-            format!("{}\\{}", config.tool.details.install_path, normalized_path)
-        };
+        config.tool.details.add_to_path.1 = path_directory;
 
+        journal
+            .record_path_entry(&config.tool.details.add_to_path.1, true)
+            .await?;
         add_to_path(&config.tool.details.add_to_path.1, true)?;
     }
 
-    let mut app_list = Library::load().await?;
-    app_list.add_tool(config.tool.clone()).await?;
-
-    app.emit("tool_install_progress", 101)?;
+    let inventory = FileInventory::scan(&config.tool.id, &config.tool.details.install_path).await?;
+    journal
+        .record_file(&FileInventory::file_path(&config.tool.id)?)
+        .await?;
+    inventory.save().await?;
 
-    Ok(config.tool.details.install_path)
+    Ok(())
 }
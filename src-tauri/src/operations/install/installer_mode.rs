@@ -1,4 +1,4 @@
-use crate::operations::extract_archive_files;
+use crate::operations::{ExtractTask, ExtractionCache, ProgressReporter};
 use anyhow::{Result, anyhow};
 use std::process::{Command, Stdio};
 use tauri::AppHandle;
@@ -9,28 +9,32 @@ pub async fn run_installer(
     password: &str,
     app: &AppHandle,
 ) -> Result<()> {
-    let timestamp = chrono::Utc::now().timestamp_millis();
-    let temp_dir = std::env::temp_dir()
-        .join("AppPorter")
-        .join(format!("installer_{timestamp}"));
-    std::fs::create_dir_all(&temp_dir)?;
-
-    extract_archive_files(
-        zip_path,
-        &temp_dir.to_string_lossy(),
-        Some(app),
-        password,
-        "installer_extract_progress",
-    )
-    .await?;
+    // The cached extraction stays leased, so it is not evicted while the installer runs
+    let cached = ExtractionCache::new()
+        .extract_archive(
+            zip_path,
+            password,
+            Some(ProgressReporter::for_app(app, ExtractTask::Installer)),
+        )
+        .await?;
 
-    let exe_full_path = temp_dir.join(executable_path.replace("/", "\\"));
+    let exe_full_path = cached.dir.join(executable_path.replace("/", "\\"));
 
     if !exe_full_path.exists() {
         return Err(anyhow!("Executable not found: {}", exe_full_path.display()));
     }
 
-    let output = Command::new(&exe_full_path)
+    let is_msi = exe_full_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("msi"));
+    let mut command = if is_msi {
+        let mut command = Command::new("msiexec");
+        command.arg("/i").arg(&exe_full_path);
+        command
+    } else {
+        Command::new(&exe_full_path)
+    };
+    let output = command
         .current_dir(
             exe_full_path
                 .parent()
@@ -41,8 +45,6 @@ pub async fn run_installer(
         .spawn()?
         .wait_with_output()?;
 
-    std::fs::remove_dir_all(&temp_dir)?;
-
     if output.stderr.is_empty() {
         Ok(())
     } else {
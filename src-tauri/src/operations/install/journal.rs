@@ -0,0 +1,374 @@
+use crate::utils::path::{path_entries, remove_from_path};
+use crate::utils::registry::{
+    RegistryValue, read_registry_entries, remove_registry_entries, restore_registry_entries,
+};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+// A change made to the system while installing, in the order it happened
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SideEffect {
+    // The directory did not exist before, rollback removes it with everything inside
+    DirectoryCreated {
+        path: String,
+    },
+    // A staging directory renamed into `target`, with the previous `target` moved to `backup`
+    DirectorySwapped {
+        staging: String,
+        target: String,
+        backup: Option<String>,
+    },
+    FileWritten {
+        path: String,
+    },
+    // A file of the same name existed, its previous contents are kept in `backup`
+    FileReplaced {
+        path: String,
+        backup: String,
+    },
+    ShortcutCreated {
+        path: String,
+    },
+    // A shortcut of the same name existed, its previous contents are kept in `backup`
+    ShortcutReplaced {
+        path: String,
+        backup: String,
+    },
+    UninstallKeyWritten {
+        name: String,
+        current_user_only: bool,
+    },
+    // The uninstall key existed, rollback writes the previous values back
+    UninstallKeyReplaced {
+        name: String,
+        current_user_only: bool,
+        values: Vec<RegistryValue>,
+    },
+    PathEntryAdded {
+        directory: String,
+        current_user_only: bool,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+struct JournalFile {
+    id: String,
+    effects: Vec<SideEffect>,
+    // Past the point of no return, only backups are left to clean up
+    committed: bool,
+}
+
+// Write-ahead log of an install, removed on commit and replayed backwards on failure.
+// Effects are recorded before they are made where possible, undoing one that never happened is a no-op.
+pub struct InstallJournal {
+    path: PathBuf,
+    file: JournalFile,
+    // Held for as long as the journal is in use, the OS releases it if the process dies
+    lock: std::fs::File,
+}
+
+// Locks `<id>.lock` next to the journal, `None` while another handle holds it
+fn try_lock(journal_path: &Path) -> Result<Option<std::fs::File>> {
+    let lock = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(journal_path.with_extension("lock"))?;
+    match lock.try_lock() {
+        Ok(()) => Ok(Some(lock)),
+        Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+        Err(std::fs::TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+impl InstallJournal {
+    fn journal_dir() -> Result<PathBuf> {
+        Ok(dirs::config_local_dir()
+            .ok_or(anyhow!("Failed to get local config directory"))?
+            .join("AppPorter")
+            .join("Journals"))
+    }
+
+    pub async fn begin(id: &str) -> Result<Self> {
+        let dir = Self::journal_dir()?;
+        fs::create_dir_all(&dir).await?;
+        let path = dir.join(format!("{id}.json"));
+        let lock = try_lock(&path)?.ok_or(anyhow!("Another install of {} is running", id))?;
+        let journal = Self {
+            path,
+            file: JournalFile {
+                id: id.to_owned(),
+                ..Default::default()
+            },
+            lock,
+        };
+        journal.save().await?;
+        Ok(journal)
+    }
+
+    pub async fn record(&mut self, effect: SideEffect) -> Result<()> {
+        self.file.effects.push(effect);
+        self.save().await
+    }
+
+    // Records a shortcut about to be written, keeping a copy of one that is already there
+    pub async fn record_shortcut(&mut self, path: &Path) -> Result<()> {
+        let path_string = path.to_string_lossy().to_string();
+        if !path.exists() {
+            return self
+                .record(SideEffect::ShortcutCreated { path: path_string })
+                .await;
+        }
+        let backup = self
+            .path
+            .with_extension(format!("{}.lnk", self.file.effects.len()));
+        fs::copy(path, &backup).await?;
+        self.record(SideEffect::ShortcutReplaced {
+            path: path_string,
+            backup: backup.to_string_lossy().to_string(),
+        })
+        .await
+    }
+
+    // Records a file about to be written, keeping a copy of one that is already there
+    pub async fn record_file(&mut self, path: &Path) -> Result<()> {
+        let path_string = path.to_string_lossy().to_string();
+        if !path.exists() {
+            return self
+                .record(SideEffect::FileWritten { path: path_string })
+                .await;
+        }
+        let backup = self
+            .path
+            .with_extension(format!("{}.bak", self.file.effects.len()));
+        fs::copy(path, &backup).await?;
+        self.record(SideEffect::FileReplaced {
+            path: path_string,
+            backup: backup.to_string_lossy().to_string(),
+        })
+        .await
+    }
+
+    // Records an uninstall key about to be written, with the values of one that is already there
+    pub async fn record_uninstall_key(
+        &mut self,
+        name: &str,
+        current_user_only: bool,
+    ) -> Result<()> {
+        let effect = match read_registry_entries(name, current_user_only)? {
+            Some(values) => SideEffect::UninstallKeyReplaced {
+                name: name.to_owned(),
+                current_user_only,
+                values,
+            },
+            None => SideEffect::UninstallKeyWritten {
+                name: name.to_owned(),
+                current_user_only,
+            },
+        };
+        self.record(effect).await
+    }
+
+    // Records a PATH entry about to be added, unless it is there already and belongs to someone else
+    pub async fn record_path_entry(
+        &mut self,
+        directory: &str,
+        current_user_only: bool,
+    ) -> Result<()> {
+        if path_entries(current_user_only)?
+            .iter()
+            .any(|entry| entry == directory.trim())
+        {
+            return Ok(());
+        }
+        self.record(SideEffect::PathEntryAdded {
+            directory: directory.to_owned(),
+            current_user_only,
+        })
+        .await
+    }
+
+    pub async fn commit(mut self) -> Result<()> {
+        self.file.committed = true;
+        self.save().await?;
+        self.finish().await
+    }
+
+    // Drops the backups kept for rollback, then the journal itself
+    async fn finish(self) -> Result<()> {
+        for effect in &self.file.effects {
+            match effect {
+                SideEffect::DirectorySwapped {
+                    backup: Some(backup),
+                    ..
+                } if Path::new(backup).exists() => fs::remove_dir_all(backup).await?,
+                SideEffect::FileReplaced { backup, .. }
+                | SideEffect::ShortcutReplaced { backup, .. }
+                    if Path::new(backup).exists() =>
+                {
+                    fs::remove_file(backup).await?
+                }
+                _ => {}
+            }
+        }
+        fs::remove_file(&self.path).await?;
+        self.release().await
+    }
+
+    // Undoes every effect in reverse; the journal stays on disk if any step fails
+    pub async fn rollback(self) -> Result<()> {
+        let mut errors = Vec::new();
+        for effect in self.file.effects.iter().rev() {
+            if let Err(e) = undo(effect).await {
+                errors.push(format!("{effect:?}: {e}"));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(anyhow!(
+                "Failed to roll back install {}: {}",
+                self.file.id,
+                errors.join("; ")
+            ));
+        }
+        fs::remove_file(&self.path).await?;
+        self.release().await
+    }
+
+    // Rolls back installs interrupted by a crash or a closed window, or finishes committed ones.
+    // Journals locked by a running install, in this process or another, are left alone.
+    pub async fn recover() -> Result<()> {
+        let Ok(mut entries) = fs::read_dir(Self::journal_dir()?).await else {
+            return Ok(());
+        };
+        // One stuck journal must not keep the others from being rolled back
+        let mut errors = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let lock = match try_lock(&path) {
+                Ok(Some(lock)) => lock,
+                Ok(None) => continue,
+                Err(e) => {
+                    errors.push(e.to_string());
+                    continue;
+                }
+            };
+            let file: JournalFile = match fs::read_to_string(&path).await {
+                Ok(content) => match serde_json::from_str(&content) {
+                    Ok(file) => file,
+                    // Kept for inspection under another name, so it is not retried on every start
+                    Err(e) => {
+                        errors.push(format!("{}: {e}", path.display()));
+                        if let Err(e) = fs::rename(&path, path.with_extension("corrupt")).await {
+                            errors.push(e.to_string());
+                        }
+                        continue;
+                    }
+                },
+                // Finished while the directory was being listed
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    errors.push(e.to_string());
+                    continue;
+                }
+            };
+            let journal = Self { path, file, lock };
+            let result = if journal.file.committed {
+                journal.finish().await
+            } else {
+                journal.rollback().await
+            };
+            if let Err(e) = result {
+                errors.push(e.to_string());
+            }
+        }
+        if !errors.is_empty() {
+            return Err(anyhow!("{}", errors.join("\n")));
+        }
+        Ok(())
+    }
+
+    // Unlocks and removes the lock file, a recovery locking it in between finds no journal
+    async fn release(self) -> Result<()> {
+        let lock_path = self.path.with_extension("lock");
+        drop(self.lock);
+        match fs::remove_file(lock_path).await {
+            // Gone already, or locked again by a recovery or the next install which removes it in turn
+            Err(e)
+                if !matches!(
+                    e.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied
+                ) =>
+            {
+                Err(e.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Written beside the journal and renamed over it, so a crash never leaves half a journal
+    async fn save(&self) -> Result<()> {
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(&self.file)?).await?;
+        fs::rename(&temp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+async fn undo(effect: &SideEffect) -> Result<()> {
+    match effect {
+        SideEffect::DirectoryCreated { path } => {
+            if Path::new(path).exists() {
+                fs::remove_dir_all(path).await?;
+            }
+        }
+        SideEffect::DirectorySwapped {
+            staging,
+            target,
+            backup,
+        } => {
+            // A staging directory still around was never moved into place
+            if !Path::new(staging).exists() && Path::new(target).exists() {
+                fs::remove_dir_all(target).await?;
+            }
+            if let Some(backup) = backup
+                && Path::new(backup).exists()
+                && !Path::new(target).exists()
+            {
+                fs::rename(backup, target).await?;
+            }
+        }
+        SideEffect::FileWritten { path } | SideEffect::ShortcutCreated { path } => {
+            if Path::new(path).exists() {
+                fs::remove_file(path).await?;
+            }
+        }
+        SideEffect::FileReplaced { path, backup }
+        | SideEffect::ShortcutReplaced { path, backup } => {
+            if Path::new(backup).exists() {
+                fs::copy(backup, path).await?;
+                fs::remove_file(backup).await?;
+            }
+        }
+        SideEffect::UninstallKeyWritten {
+            name,
+            current_user_only,
+        } => remove_registry_entries(name, *current_user_only)?,
+        SideEffect::UninstallKeyReplaced {
+            name,
+            current_user_only,
+            values,
+        } => restore_registry_entries(name, *current_user_only, values)?,
+        SideEffect::PathEntryAdded {
+            directory,
+            current_user_only,
+        } => remove_from_path(directory, *current_user_only)?,
+    }
+    Ok(())
+}
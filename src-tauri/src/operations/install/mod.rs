@@ -1,12 +1,18 @@
+pub mod conflicts;
 pub mod install_app;
 pub mod install_tool;
 pub mod installer_mode;
+pub mod journal;
+pub mod staging;
 
 use anyhow::{Result, anyhow};
+pub use conflicts::*;
 use futures_util::future::BoxFuture;
 pub use install_app::*;
 pub use install_tool::*;
 pub use installer_mode::*;
+pub use journal::*;
+pub use staging::*;
 
 use std::path::Path;
 use tokio::fs;
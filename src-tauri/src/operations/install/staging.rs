@@ -0,0 +1,118 @@
+use super::{InstallJournal, SideEffect};
+use crate::operations::{FileInventory, walk_files};
+use anyhow::{Result, anyhow};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+// Siblings of the install directory, so moving them into place is a rename on the same volume
+fn sibling(install_path: &str, suffix: &str) -> Result<PathBuf> {
+    let target = Path::new(install_path);
+    let parent = target
+        .parent()
+        .ok_or(anyhow!("Install path has no parent directory"))?;
+    let name = target
+        .file_name()
+        .ok_or(anyhow!("Install path has no directory name"))?
+        .to_string_lossy();
+    Ok(parent.join(format!(".{name}.{suffix}")))
+}
+
+impl InstallJournal {
+    // Creates an empty staging directory for `install_path` and returns it
+    pub async fn stage(&mut self, install_path: &str) -> Result<PathBuf> {
+        let staging = sibling(install_path, "staging")?;
+        // Leftovers of an unjournaled run hold nothing worth keeping
+        if staging.exists() {
+            fs::remove_dir_all(&staging).await?;
+        }
+
+        // The topmost missing ancestor is what rollback has to remove
+        let mut created = staging.as_path();
+        while let Some(parent) = created.parent()
+            && !parent.as_os_str().is_empty()
+            && !parent.exists()
+        {
+            created = parent;
+        }
+        self.record(SideEffect::DirectoryCreated {
+            path: created.to_string_lossy().to_string(),
+        })
+        .await?;
+        fs::create_dir_all(&staging).await?;
+        Ok(staging)
+    }
+
+    // Renames the staging directory to `install_path`, the previous one is kept until commit
+    pub async fn swap_into_place(&mut self, staging: &Path, install_path: &str) -> Result<()> {
+        let backup = sibling(install_path, "backup")?;
+        if backup.exists() {
+            fs::remove_dir_all(&backup).await?;
+        }
+        let replaces = Path::new(install_path).exists();
+        self.record(SideEffect::DirectorySwapped {
+            staging: staging.to_string_lossy().to_string(),
+            target: install_path.to_owned(),
+            backup: replaces.then(|| backup.to_string_lossy().to_string()),
+        })
+        .await?;
+
+        if replaces {
+            fs::rename(install_path, &backup).await?;
+        }
+        if let Err(e) = fs::rename(staging, install_path).await {
+            if replaces {
+                fs::rename(&backup, install_path).await?;
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+// Where a path below the staging directory ends up once it is in place
+pub fn unstaged_path(path: &str, staging: &Path, install_path: &str) -> Result<String> {
+    Ok(Path::new(install_path)
+        .join(Path::new(path).strip_prefix(staging)?)
+        .to_string_lossy()
+        .to_string())
+}
+
+// Copies files the previous install did not ship, such as settings saved next to the app, into
+// the staging directory. Without an inventory every old file the new one lacks is kept.
+pub async fn carry_over_user_files(
+    install_path: &str,
+    staging: &Path,
+    inventory: Option<&FileInventory>,
+) -> Result<()> {
+    // Lowercased relative paths, looked up once per file of the install directory
+    let installed: HashSet<String> = inventory
+        .map(|inventory| {
+            inventory
+                .files
+                .iter()
+                .map(|file| file.path.to_lowercase())
+                .collect()
+        })
+        .unwrap_or_default();
+    let source = PathBuf::from(install_path);
+    let staging = staging.to_owned();
+
+    tokio::task::spawn_blocking(move || {
+        if !source.is_dir() {
+            return Ok(());
+        }
+        for (path, _) in walk_files(&source)? {
+            let target = staging.join(&path);
+            if installed.contains(&path.to_lowercase()) || target.exists() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(source.join(&path), target)?;
+        }
+        Ok(())
+    })
+    .await?
+}
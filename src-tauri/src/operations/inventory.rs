@@ -0,0 +1,182 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use ts_rs::TS;
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct InventoryEntry {
+    // Relative to the install directory, with `\` separators
+    pub path: String,
+    #[ts(type = "number")]
+    pub size: u64,
+    pub sha256: String,
+    // Milliseconds since the Unix epoch
+    #[ts(type = "number")]
+    pub modified: i64,
+}
+
+// Every file an install or reinstall put on disk, kept next to the library as `Inventories\<id>.json`
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct FileInventory {
+    pub id: String,
+    pub root: String,
+    pub recorded: String,
+    pub files: Vec<InventoryEntry>,
+}
+
+impl FileInventory {
+    pub fn file_path(id: &str) -> Result<PathBuf> {
+        Ok(dirs::config_local_dir()
+            .ok_or(anyhow!("Failed to get local config directory"))?
+            .join("AppPorter")
+            .join("Inventories")
+            .join(format!("{id}.json")))
+    }
+
+    // Hashes everything below `root`
+    pub async fn scan(id: &str, root: &str) -> Result<Self> {
+        let root_path = PathBuf::from(root);
+        let files = tokio::task::spawn_blocking(move || {
+            walk_files(&root_path)?
+                .into_iter()
+                .map(|(path, metadata)| {
+                    Ok(InventoryEntry {
+                        sha256: hash_file(&root_path.join(&path))?,
+                        size: metadata.len(),
+                        modified: modified_millis(&metadata)?,
+                        path,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .await??;
+
+        Ok(Self {
+            id: id.to_owned(),
+            root: root.to_owned(),
+            recorded: chrono::Utc::now().to_rfc3339(),
+            files,
+        })
+    }
+
+    // Installs from before inventories were recorded have none
+    pub async fn load(id: &str) -> Result<Option<Self>> {
+        let path = Self::file_path(id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(
+            &tokio::fs::read_to_string(path).await?,
+        )?))
+    }
+
+    pub async fn save(&self) -> Result<()> {
+        let path = Self::file_path(&self.id)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, serde_json::to_string(self)?).await?;
+        Ok(())
+    }
+
+    pub async fn delete(id: &str) -> Result<()> {
+        let path = Self::file_path(id)?;
+        if path.exists() {
+            tokio::fs::remove_file(path).await?;
+        }
+        Ok(())
+    }
+
+    // Removes the listed files and the folders they leave empty, anything added later stays
+    pub async fn remove_files(&self) -> Result<()> {
+        let root = PathBuf::from(&self.root);
+        let files: Vec<PathBuf> = self
+            .files
+            .iter()
+            .map(|file| root.join(&file.path))
+            .collect();
+        tokio::task::spawn_blocking(move || {
+            for file in &files {
+                if file.is_file() {
+                    fs::remove_file(file)?;
+                }
+            }
+            remove_empty_dirs(&root)?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+pub fn modified_millis(metadata: &fs::Metadata) -> Result<i64> {
+    Ok(metadata.modified()?.duration_since(UNIX_EPOCH)?.as_millis() as i64)
+}
+
+// Files below `root` as relative `\` paths, sorted
+pub fn walk_files(root: &Path) -> Result<Vec<(String, fs::Metadata)>> {
+    fn walk(root: &Path, dir: &Path, files: &mut Vec<(String, fs::Metadata)>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                walk(root, &path, files)?;
+            } else if file_type.is_file() {
+                let relative = path
+                    .strip_prefix(root)?
+                    .to_string_lossy()
+                    .replace('/', "\\");
+                files.push((relative, entry.metadata()?));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    walk(root, root, &mut files)?;
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+// Returns whether `dir` itself was removed
+fn remove_empty_dirs(dir: &Path) -> Result<bool> {
+    if !dir.is_dir() {
+        return Ok(false);
+    }
+    let mut empty = true;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !(entry.file_type()?.is_dir() && remove_empty_dirs(&entry.path())?) {
+            empty = false;
+        }
+    }
+    if empty {
+        fs::remove_dir(dir)?;
+    }
+    Ok(empty)
+}
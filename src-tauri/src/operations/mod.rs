@@ -1,25 +1,33 @@
 pub mod archive;
+pub mod exe_suggestions;
 pub mod get_details;
 pub mod install;
+pub mod inventory;
 pub mod launcher;
 pub mod modify;
+pub mod package_kind;
 pub mod preview;
 pub mod reinstall;
 pub mod repair;
 pub mod uninstall;
 pub mod update;
 pub mod validation;
+pub mod verify;
 pub mod zip_preview;
 
 pub use archive::*;
+pub use exe_suggestions::*;
 pub use get_details::*;
 pub use install::*;
+pub use inventory::*;
 pub use launcher::*;
 pub use modify::*;
+pub use package_kind::*;
 pub use preview::*;
 pub use reinstall::*;
 pub use repair::*;
 pub use uninstall::*;
 pub use update::*;
 pub use validation::*;
+pub use verify::*;
 pub use zip_preview::*;
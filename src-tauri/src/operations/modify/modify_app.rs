@@ -1,4 +1,5 @@
 use crate::configs::{ConfigFile, library::*};
+use crate::operations::FileInventory;
 use crate::utils::convert_base64_to_ico;
 use crate::utils::path::{add_to_path, remove_from_path};
 use crate::utils::registry::{create_registry_entries, remove_registry_entries};
@@ -10,12 +11,15 @@ use anyhow::{Result, anyhow};
 use mslnk::ShellLink;
 use std::path::Path;
 
-pub async fn modify_app(new_app: App, id: &str) -> Result<()> {
+pub async fn modify_app(mut new_app: App, id: &str) -> Result<()> {
     let mut library = Library::load().await?;
     let old_app = library
         .get_app(id)
         .await
         .ok_or(anyhow!("App with ID {} not found", id))?;
+    // The signature and the extraction filter are recorded at install time and are not editable
+    new_app.details.unverified_signature = old_app.details.unverified_signature.clone();
+    new_app.details.filter = old_app.details.filter.clone();
 
     if old_app.details.install_path != new_app.details.install_path
         && Path::new(&old_app.details.install_path).exists()
@@ -28,6 +32,10 @@ pub async fn modify_app(new_app: App, id: &str) -> Result<()> {
                 .overwrite(true)
                 .content_only(true),
         )?;
+        if let Some(mut inventory) = FileInventory::load(id).await? {
+            inventory.root = new_app.details.install_path.clone();
+            inventory.save().await?;
+        }
     }
 
     if old_app.details.config.add_to_path != new_app.details.config.add_to_path {
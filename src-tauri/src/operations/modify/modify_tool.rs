@@ -1,15 +1,18 @@
 use crate::configs::{ConfigFile, library::*};
+use crate::operations::FileInventory;
 use crate::utils::path::{add_to_path, remove_from_path};
 use anyhow::{Result, anyhow};
 use std::path::Path;
 use tokio::fs as tokio_fs;
 
-pub async fn modify_tool(new_tool: Tool, id: &str) -> Result<()> {
+pub async fn modify_tool(mut new_tool: Tool, id: &str) -> Result<()> {
     let mut library = Library::load().await?;
     let old_tool = library
         .get_tool(id)
         .await
         .ok_or(anyhow!("Tool with ID {} not found", id))?;
+    // The extraction filter is recorded at install time and is not editable
+    new_tool.details.filter = old_tool.details.filter.clone();
 
     if old_tool.details.install_path != new_tool.details.install_path
         && Path::new(&old_tool.details.install_path).exists()
@@ -22,6 +25,10 @@ pub async fn modify_tool(new_tool: Tool, id: &str) -> Result<()> {
                 .overwrite(true)
                 .content_only(true),
         )?;
+        if let Some(mut inventory) = FileInventory::load(id).await? {
+            inventory.root = new_tool.details.install_path.clone();
+            inventory.save().await?;
+        }
     }
 
     if old_tool.details.add_to_path != new_tool.details.add_to_path {
@@ -0,0 +1,288 @@
+use super::{ArchiveEntry, open_payload, rank_executables, saved_password};
+use crate::utils::pe::{PeFile, PeSubsystem};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Installers ship with little else; a big listing means the installer is probably a bundled extra
+const INSTALLER_ARCHIVE_FILES: usize = 20;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageKind {
+    Nsis,
+    InnoSetup,
+    Msi,
+    WixBurn,
+    Squirrel,
+    InstallShield,
+    ConsoleTools,
+    PortableGui,
+    // Data, fonts, scripts and the like, copied into place as a tool
+    NoExecutables,
+    Unknown,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallMode {
+    App,
+    Tool,
+    Installer,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct PackageClassification {
+    pub kind: PackageKind,
+    // 0-100
+    pub confidence: u8,
+    pub evidence: Vec<String>,
+    pub install_mode: InstallMode,
+    // Installer to run, or the main executable of an app
+    pub executable: Option<String>,
+}
+
+impl PackageKind {
+    pub fn install_mode(&self) -> InstallMode {
+        match self {
+            Self::ConsoleTools | Self::NoExecutables => InstallMode::Tool,
+            Self::PortableGui | Self::Unknown => InstallMode::App,
+            _ => InstallMode::Installer,
+        }
+    }
+}
+
+struct Marker {
+    kind: PackageKind,
+    // Markers found inside a binary are stronger than file names in the listing
+    from_binary: bool,
+    evidence: String,
+    executable: Option<String>,
+}
+
+// Decides whether an archive holds a portable app, a set of console tools or an installer
+pub async fn classify_package(path: &str, password: Option<&str>) -> Result<PackageClassification> {
+    let password = match password {
+        Some(password) => password.to_owned(),
+        None => saved_password(path).await?,
+    };
+    let payload = open_payload(path, Some(&password)).await?;
+    let (suggestions, inspected) = rank_executables(path, &payload, &password).await?;
+
+    let mut markers = listing_markers(&payload.entries);
+    for executable in &inspected {
+        let name = executable.path.to_lowercase();
+        // Bundled runtimes and uninstallers carry installer markers without making the package one
+        if name.contains("unins") || name.contains("redist") {
+            continue;
+        }
+        markers.extend(
+            binary_markers(&executable.pe)
+                .into_iter()
+                .map(|(kind, evidence)| Marker {
+                    kind,
+                    from_binary: true,
+                    evidence: format!("{}: {evidence}", executable.path),
+                    executable: Some(executable.path.clone()),
+                }),
+        );
+    }
+
+    let file_count = payload.entries.iter().filter(|entry| !entry.is_dir).count();
+    if let Some(classification) = best_installer(markers, file_count) {
+        return Ok(classification);
+    }
+
+    let Some(top) = suggestions.first() else {
+        let kind = PackageKind::NoExecutables;
+        return Ok(PackageClassification {
+            kind,
+            confidence: 30,
+            evidence: vec!["No executables in archive".to_owned()],
+            install_mode: kind.install_mode(),
+            executable: None,
+        });
+    };
+
+    let executable_count = suggestions.len();
+    let console_count = inspected
+        .iter()
+        .filter(|executable| executable.pe.subsystem == PeSubsystem::Console)
+        .count();
+    if !inspected.is_empty() && console_count == inspected.len() {
+        let all_inspected = inspected.len() == executable_count;
+        let kind = PackageKind::ConsoleTools;
+        return Ok(PackageClassification {
+            kind,
+            confidence: if all_inspected { 80 } else { 60 },
+            evidence: vec![format!(
+                "{console_count} of {executable_count} executable(s) checked, all console applications"
+            )],
+            install_mode: kind.install_mode(),
+            executable: None,
+        });
+    }
+
+    let top_subsystem = inspected
+        .iter()
+        .find(|executable| executable.path == top.path)
+        .map(|executable| executable.pe.subsystem);
+    let mut evidence: Vec<String> = top
+        .reasons
+        .iter()
+        .map(|reason| format!("{}: {}", top.path, reason.detail))
+        .collect();
+    let (kind, confidence) = match top_subsystem {
+        Some(PeSubsystem::Gui) => (PackageKind::PortableGui, 75),
+        Some(_) => {
+            evidence.push(format!("{}: not a GUI application", top.path));
+            (PackageKind::Unknown, 40)
+        }
+        None => {
+            evidence.push(format!("{}: PE headers could not be read", top.path));
+            (PackageKind::Unknown, 40)
+        }
+    };
+    Ok(PackageClassification {
+        kind,
+        confidence,
+        evidence,
+        install_mode: kind.install_mode(),
+        executable: Some(top.path.clone()),
+    })
+}
+
+fn best_installer(markers: Vec<Marker>, file_count: usize) -> Option<PackageClassification> {
+    let mut kinds: Vec<PackageKind> = Vec::new();
+    for marker in &markers {
+        if !kinds.contains(&marker.kind) {
+            kinds.push(marker.kind);
+        }
+    }
+
+    let mut best: Option<PackageClassification> = None;
+    for kind in kinds {
+        let matching: Vec<&Marker> = markers
+            .iter()
+            .filter(|marker| marker.kind == kind)
+            .collect();
+        let from_binary = matching.iter().any(|marker| marker.from_binary);
+        let from_listing = matching.iter().any(|marker| !marker.from_binary);
+
+        let mut confidence: u8 = match (from_binary, from_listing) {
+            (true, true) => 95,
+            (true, false) => 85,
+            _ => (50 + 10 * matching.len()).min(80) as u8,
+        };
+        let mut evidence: Vec<String> = matching
+            .iter()
+            .map(|marker| marker.evidence.clone())
+            .collect();
+        if file_count > INSTALLER_ARCHIVE_FILES {
+            confidence = confidence.saturating_sub(25);
+            evidence.push(format!("Archive also holds {file_count} files"));
+        }
+
+        if best
+            .as_ref()
+            .is_none_or(|best| confidence > best.confidence)
+        {
+            best = Some(PackageClassification {
+                kind,
+                confidence,
+                evidence,
+                install_mode: kind.install_mode(),
+                executable: matching.iter().find_map(|marker| marker.executable.clone()),
+            });
+        }
+    }
+
+    best
+}
+
+fn listing_markers(entries: &[ArchiveEntry]) -> Vec<Marker> {
+    let mut markers = Vec::new();
+    let shallowest = |suffix: &str| {
+        entries
+            .iter()
+            .filter(|entry| !entry.is_dir && entry.path.to_lowercase().ends_with(suffix))
+            .min_by_key(|entry| entry.path.matches('\\').count())
+            .map(|entry| entry.path.clone())
+    };
+    let has_name = |name: &str| {
+        entries
+            .iter()
+            .any(|entry| entry.name().eq_ignore_ascii_case(name))
+    };
+
+    if let Some(msi) = shallowest(".msi") {
+        markers.push(Marker {
+            kind: PackageKind::Msi,
+            from_binary: false,
+            evidence: format!("Windows Installer package {msi}"),
+            executable: Some(msi),
+        });
+    }
+
+    if has_name("RELEASES") && shallowest(".nupkg").is_some() {
+        markers.push(Marker {
+            kind: PackageKind::Squirrel,
+            from_binary: false,
+            evidence: "RELEASES file next to .nupkg packages".to_owned(),
+            executable: shallowest("setup.exe"),
+        });
+    }
+
+    for name in ["setup.inx", "data1.hdr", "ISSetup.dll", "_setup.dll"] {
+        if has_name(name) {
+            markers.push(Marker {
+                kind: PackageKind::InstallShield,
+                from_binary: false,
+                evidence: format!("InstallShield file {name}"),
+                executable: shallowest("setup.exe"),
+            });
+        }
+    }
+
+    if entries
+        .iter()
+        .any(|entry| entry.path.split('\\').any(|part| part == "$PLUGINSDIR"))
+    {
+        markers.push(Marker {
+            kind: PackageKind::Nsis,
+            from_binary: false,
+            evidence: "NSIS $PLUGINSDIR folder".to_owned(),
+            executable: None,
+        });
+    }
+
+    markers
+}
+
+fn binary_markers(pe: &PeFile) -> Vec<(PackageKind, String)> {
+    let mut markers = Vec::new();
+    if pe.sections.iter().any(|section| section.name == ".wixburn") {
+        markers.push((PackageKind::WixBurn, "WiX burn .wixburn section".to_owned()));
+    }
+
+    // Looked up both as ASCII and as UTF-16, the way resources and wide literals store them
+    const SIGNATURES: [(&str, PackageKind); 4] = [
+        ("NullsoftInst", PackageKind::Nsis),
+        ("Inno Setup Setup Data", PackageKind::InnoSetup),
+        ("InstallShield", PackageKind::InstallShield),
+        ("SquirrelTemp", PackageKind::Squirrel),
+    ];
+    for (signature, kind) in SIGNATURES {
+        let wide: Vec<u8> = signature
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        if pe.contains(signature.as_bytes()) || pe.contains(&wide) {
+            markers.push((kind, format!("Contains the string '{signature}'")));
+        }
+    }
+    markers
+}
@@ -1,10 +1,11 @@
+use crate::operations::ensure_archive_extension;
 use crate::utils::download_file;
 use anyhow::Result;
 use tauri::{AppHandle, Emitter};
 use uuid::Uuid;
 
 pub async fn preview_url(app: &AppHandle, url: &str) -> Result<()> {
-    let downloaded = download_file(url).await?;
+    let downloaded = ensure_archive_extension(&download_file(url).await?).await?;
     app.emit(
         "preview_url",
         (
@@ -1,46 +1,70 @@
 use crate::configs::library::*;
-use crate::operations::install::flatten_nested_folders;
-use crate::operations::{extract_archive_files, repair_app};
+use crate::operations::install::{InstallJournal, carry_over_user_files, flatten_nested_folders};
+use crate::operations::{
+    ExtractTask, FileInventory, ProgressReporter, ensure_archive_intact, extract_archive_files,
+    repair_app,
+};
 use anyhow::{Result, anyhow};
-use fs_extra::dir::move_dir;
+use tauri::AppHandle;
 
-pub async fn reinstall_app(id: &str, zip_path: &str) -> Result<()> {
+pub async fn reinstall_app(id: &str, zip_path: &str, app: &AppHandle) -> Result<()> {
     let library = Library::load().await?;
     let app_config = library
         .get_app(id)
         .await
         .ok_or(anyhow!("App with ID {} not found", id))?;
 
-    let temp_dir = std::env::temp_dir()
-        .join("AppPorter")
-        .join("Reinstall")
-        .join(format!(
-            "{}-{}",
-            app_config.details.info.name, app_config.id
-        ));
+    ensure_archive_intact(zip_path, &app_config.archive_password).await?;
 
-    tokio::fs::create_dir_all(&temp_dir).await?;
+    let mut journal = InstallJournal::begin(id).await?;
+    if let Err(e) = reinstall_app_files(id, zip_path, &app_config, app, &mut journal).await {
+        if let Err(rollback_error) = journal.rollback().await {
+            eprintln!("{rollback_error}");
+        }
+        return Err(e);
+    }
+    journal.commit().await?;
+
+    Ok(())
+}
+
+// Extracts into a staging directory, swaps it in and replaces the recorded inventory
+async fn reinstall_app_files(
+    id: &str,
+    zip_path: &str,
+    app_config: &App,
+    app: &AppHandle,
+    journal: &mut InstallJournal,
+) -> Result<()> {
+    let install_path = &app_config.details.install_path;
+    let staging = journal.stage(install_path).await?;
     extract_archive_files(
         zip_path,
-        &temp_dir.to_string_lossy(),
-        None,
+        &staging.to_string_lossy(),
+        &app_config.details.filter,
+        Some(ProgressReporter::for_app(app, ExtractTask::AppReinstall)),
         &app_config.archive_password,
-        "",
     )
     .await?;
 
-    flatten_nested_folders(&temp_dir.to_string_lossy(), None).await?;
+    flatten_nested_folders(&staging.to_string_lossy(), None).await?;
 
-    tokio::fs::create_dir_all(&app_config.details.install_path).await?;
-    move_dir(
-        &temp_dir,
-        &app_config.details.install_path,
-        &fs_extra::dir::CopyOptions::new()
-            .overwrite(true)
-            .content_only(true),
-    )?;
+    // Taken before user files join the new ones, so they stay out of the inventory
+    let mut inventory = FileInventory::scan(id, &staging.to_string_lossy()).await?;
+    inventory.root = install_path.clone();
+    carry_over_user_files(
+        install_path,
+        &staging,
+        FileInventory::load(id).await?.as_ref(),
+    )
+    .await?;
+
+    journal.swap_into_place(&staging, install_path).await?;
 
     repair_app(id).await?;
 
+    journal.record_file(&FileInventory::file_path(id)?).await?;
+    inventory.save().await?;
+
     Ok(())
 }
@@ -1,44 +1,71 @@
 use crate::{
     configs::library::*,
-    operations::{extract_archive_files, flatten_nested_folders, repair_tool},
+    operations::{
+        ExtractTask, FileInventory, InstallJournal, ProgressReporter, carry_over_user_files,
+        ensure_archive_intact, extract_archive_files, flatten_nested_folders, repair_tool,
+    },
 };
 use anyhow::{Result, anyhow};
-use fs_extra::dir::move_dir;
+use tauri::AppHandle;
 
-pub async fn reinstall_tool(id: &str, zip_path: &str) -> Result<()> {
+pub async fn reinstall_tool(id: &str, zip_path: &str, app: &AppHandle) -> Result<()> {
     let library = Library::load().await?;
     let tool_config = library
         .get_tool(id)
         .await
         .ok_or(anyhow!("Tool with ID {} not found", id))?;
 
-    let temp_dir = std::env::temp_dir()
-        .join("AppPorter")
-        .join("reinstall")
-        .join(format!("{}-{}", tool_config.details.name, tool_config.id));
+    ensure_archive_intact(zip_path, &tool_config.archive_password).await?;
 
-    tokio::fs::create_dir_all(&temp_dir).await?;
+    let mut journal = InstallJournal::begin(id).await?;
+    if let Err(e) = reinstall_tool_files(id, zip_path, &tool_config, app, &mut journal).await {
+        if let Err(rollback_error) = journal.rollback().await {
+            eprintln!("{rollback_error}");
+        }
+        return Err(e);
+    }
+    journal.commit().await?;
+
+    Ok(())
+}
+
+// Extracts into a staging directory, swaps it in and replaces the recorded inventory
+async fn reinstall_tool_files(
+    id: &str,
+    zip_path: &str,
+    tool_config: &Tool,
+    app: &AppHandle,
+    journal: &mut InstallJournal,
+) -> Result<()> {
+    let install_path = &tool_config.details.install_path;
+    let staging = journal.stage(install_path).await?;
     extract_archive_files(
         zip_path,
-        &temp_dir.to_string_lossy(),
-        None,
+        &staging.to_string_lossy(),
+        &tool_config.details.filter,
+        Some(ProgressReporter::for_app(app, ExtractTask::ToolReinstall)),
         &tool_config.archive_password,
-        "",
     )
     .await?;
 
-    flatten_nested_folders(&temp_dir.to_string_lossy(), None).await?;
+    flatten_nested_folders(&staging.to_string_lossy(), None).await?;
 
-    tokio::fs::create_dir_all(&tool_config.details.install_path).await?;
-    move_dir(
-        &temp_dir,
-        &tool_config.details.install_path,
-        &fs_extra::dir::CopyOptions::new()
-            .overwrite(true)
-            .content_only(true),
-    )?;
+    // Taken before user files join the new ones, so they stay out of the inventory
+    let mut inventory = FileInventory::scan(id, &staging.to_string_lossy()).await?;
+    inventory.root = install_path.clone();
+    carry_over_user_files(
+        install_path,
+        &staging,
+        FileInventory::load(id).await?.as_ref(),
+    )
+    .await?;
+
+    journal.swap_into_place(&staging, install_path).await?;
 
     repair_tool(id).await?;
 
+    journal.record_file(&FileInventory::file_path(id)?).await?;
+    inventory.save().await?;
+
     Ok(())
 }
@@ -1,4 +1,5 @@
 use crate::configs::library::Library;
+use crate::operations::FileInventory;
 use crate::utils::path::remove_from_path;
 use crate::utils::registry::remove_registry_entries;
 use crate::utils::shortcuts::{
@@ -15,9 +16,15 @@ pub async fn uninstall_app(id: &str) -> Result<()> {
         .await
         .ok_or(anyhow!("App with ID {} not found", id))?;
 
-    let app_path = &app_config.details.install_path;
-    if Path::new(app_path).exists() {
-        fs::remove_dir_all(app_path).await?;
+    // Only what the install recorded is removed, older installs drop the whole directory
+    if let Some(inventory) = FileInventory::load(id).await? {
+        inventory.remove_files().await?;
+        FileInventory::delete(id).await?;
+    } else {
+        let app_path = &app_config.details.install_path;
+        if Path::new(app_path).exists() {
+            fs::remove_dir_all(app_path).await?;
+        }
     }
 
     if app_config.details.config.create_desktop_shortcut {
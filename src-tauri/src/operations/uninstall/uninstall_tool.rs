@@ -1,4 +1,5 @@
 use crate::configs::library::Library;
+use crate::operations::FileInventory;
 use crate::utils::path::remove_from_path;
 use anyhow::{Result, anyhow};
 use std::path::Path;
@@ -11,9 +12,15 @@ pub async fn uninstall_tool(id: &str) -> Result<()> {
         .await
         .ok_or(anyhow!("Tool with ID {} not found", id))?;
 
-    let tool_path = &tool_config.details.install_path;
-    if Path::new(tool_path).exists() {
-        fs::remove_dir_all(tool_path).await?;
+    // Only what the install recorded is removed, older installs drop the whole directory
+    if let Some(inventory) = FileInventory::load(id).await? {
+        inventory.remove_files().await?;
+        FileInventory::delete(id).await?;
+    } else {
+        let tool_path = &tool_config.details.install_path;
+        if Path::new(tool_path).exists() {
+            fs::remove_dir_all(tool_path).await?;
+        }
     }
 
     if tool_config.details.add_to_path.0 {
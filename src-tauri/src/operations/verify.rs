@@ -0,0 +1,82 @@
+use super::{FileInventory, hash_file, modified_millis, walk_files};
+use crate::configs::library::Library;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use ts_rs::TS;
+
+// Paths are relative to the install directory
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct VerifyReport {
+    #[ts(type = "number")]
+    pub checked: u64,
+    pub missing: Vec<String>,
+    pub modified: Vec<String>,
+    pub unexpected: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.modified.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+pub async fn verify_app(id: &str, fast: bool) -> Result<VerifyReport> {
+    let library = Library::load().await?;
+    let app = library
+        .get_app(id)
+        .await
+        .ok_or(anyhow!("App with ID {} not found", id))?;
+    verify_install(id, &app.details.install_path, fast).await
+}
+
+pub async fn verify_tool(id: &str, fast: bool) -> Result<VerifyReport> {
+    let library = Library::load().await?;
+    let tool = library
+        .get_tool(id)
+        .await
+        .ok_or(anyhow!("Tool with ID {} not found", id))?;
+    verify_install(id, &tool.details.install_path, fast).await
+}
+
+// Compares the install directory with its inventory; fast mode trusts matching size and mtime
+async fn verify_install(id: &str, install_path: &str, fast: bool) -> Result<VerifyReport> {
+    let inventory = FileInventory::load(id)
+        .await?
+        .ok_or(anyhow!("No file inventory was recorded for this install"))?;
+    let root = PathBuf::from(install_path);
+
+    tokio::task::spawn_blocking(move || {
+        // Windows paths compare case-insensitively
+        let mut on_disk: HashMap<String, (String, std::fs::Metadata)> = walk_files(&root)?
+            .into_iter()
+            .map(|(path, metadata)| (path.to_lowercase(), (path, metadata)))
+            .collect();
+
+        let mut report = VerifyReport::default();
+        for expected in &inventory.files {
+            report.checked += 1;
+            let Some((_, metadata)) = on_disk.remove(&expected.path.to_lowercase()) else {
+                report.missing.push(expected.path.clone());
+                continue;
+            };
+            let changed = if metadata.len() != expected.size {
+                true
+            } else if fast {
+                modified_millis(&metadata)? != expected.modified
+            } else {
+                hash_file(&root.join(&expected.path))? != expected.sha256
+            };
+            if changed {
+                report.modified.push(expected.path.clone());
+            }
+        }
+
+        report.unexpected = on_disk.into_values().map(|(path, _)| path).collect();
+        report.unexpected.sort();
+        Ok(report)
+    })
+    .await?
+}
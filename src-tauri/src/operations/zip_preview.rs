@@ -1,9 +1,31 @@
-use crate::operations::get_archive_content;
-use anyhow::Result;
+use crate::configs::{ConfigFile, settings::Settings};
+use crate::operations::{
+    ArchiveEntry, is_archive_file, password_fingerprint, resolve_volume_path, saved_password,
+    unwrap_nested_archive,
+};
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use ts_rs::TS;
 
+lazy_static::lazy_static! {
+    // Tree of the archive previewed last, so expanding a directory does not list it all over again
+    static ref LAST_TREE: Mutex<Option<(TreeKey, Arc<FileTree>)>> = Mutex::new(None);
+}
+
+// Everything a listing depends on: the archive as it is on disk, the password and the unwrap depth
+#[derive(PartialEq, Eq)]
+struct TreeKey {
+    path: String,
+    size: u64,
+    modified: SystemTime,
+    password: String,
+    unwrap_depth: u32,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
 #[ts(export)]
 pub struct FileTreeNode {
@@ -14,184 +36,237 @@ pub struct FileTreeNode {
     pub children: Option<Vec<FileTreeNode>>,
     pub expanded: bool,
     pub level: u32,
+    // Total uncompressed size of everything below a directory
+    #[ts(type = "number")]
+    pub size: u64,
+    // Number of files below a directory, 1 for a file
+    #[ts(type = "number")]
+    pub file_count: u64,
+    // Directory whose children were left out by the depth limit
+    pub truncated: bool,
+    pub modified: String,
+    pub encrypted: bool,
 }
 
-pub fn build_file_tree(paths: Vec<String>) -> Vec<FileTreeNode> {
-    let mut dir_map = HashMap::new();
-    let mut all_nodes = HashMap::new();
-
-    for path in &paths {
-        let parts: Vec<&str> = path.split('\\').collect();
-        let mut current_path = String::new();
-        for part in parts.iter().take(parts.len().saturating_sub(1)) {
-            if part.is_empty() {
-                continue;
-            }
-            current_path = if current_path.is_empty() {
-                part.to_string()
-            } else {
-                format!("{current_path}\\{part}")
-            };
-            dir_map.insert(current_path.clone(), true);
-        }
-    }
-
-    for path in paths {
-        if path.trim().is_empty() {
-            continue;
-        }
+// Flat trie over the entry list, children only ever point at later indices
+struct TrieNode {
+    name: String,
+    path: String,
+    is_dir: bool,
+    parent: usize,
+    entry: Option<usize>,
+    children: HashMap<String, usize>,
+    size: u64,
+    file_count: u64,
+}
 
-        let parts: Vec<&str> = path.split('\\').filter(|p| !p.is_empty()).collect();
-        if parts.is_empty() {
-            continue;
-        }
+pub struct FileTree {
+    entries: Vec<ArchiveEntry>,
+    nodes: Vec<TrieNode>,
+}
 
-        let mut current_path = String::new();
+impl FileTree {
+    pub fn new(entries: Vec<ArchiveEntry>) -> Self {
+        let mut nodes = vec![TrieNode {
+            name: String::new(),
+            path: String::new(),
+            is_dir: true,
+            parent: 0,
+            entry: None,
+            children: HashMap::new(),
+            size: 0,
+            file_count: 0,
+        }];
 
-        for (index, part) in parts.iter().enumerate() {
-            current_path = if current_path.is_empty() {
-                part.to_string()
-            } else {
-                format!("{current_path}\\{part}")
-            };
-
-            let is_last = index == parts.len() - 1;
-            let is_file = is_last && !dir_map.contains_key(&current_path);
-
-            if !all_nodes.contains_key(&current_path) {
-                let node = FileTreeNode {
-                    key: current_path.clone(),
-                    name: part.to_string(),
-                    path: current_path.clone(),
-                    node_type: if is_file {
-                        "file".to_owned()
-                    } else {
-                        "directory".to_owned()
-                    },
-                    level: index as u32,
-                    children: if is_file { None } else { Some(Vec::new()) },
-                    expanded: false,
+        for (index, entry) in entries.iter().enumerate() {
+            let parts: Vec<&str> = entry.path.split('\\').filter(|p| !p.is_empty()).collect();
+            let mut current = 0;
+            for (depth, part) in parts.iter().enumerate() {
+                let is_last = depth == parts.len() - 1;
+                current = match nodes[current].children.get(*part) {
+                    Some(&child) => child,
+                    None => {
+                        let path = if current == 0 {
+                            part.to_string()
+                        } else {
+                            format!("{}\\{part}", nodes[current].path)
+                        };
+                        nodes.push(TrieNode {
+                            name: part.to_string(),
+                            path,
+                            is_dir: !is_last,
+                            parent: current,
+                            entry: None,
+                            children: HashMap::new(),
+                            size: 0,
+                            file_count: 0,
+                        });
+                        let child = nodes.len() - 1;
+                        nodes[current].children.insert(part.to_string(), child);
+                        child
+                    }
                 };
-                all_nodes.insert(current_path.clone(), node);
-            }
-        }
-    }
-
-    let mut root_nodes = Vec::new();
-    let mut sorted_paths: Vec<String> = all_nodes.keys().cloned().collect();
-    sorted_paths.sort();
-
-    for path in sorted_paths {
-        if let Some(node) = all_nodes.remove(&path) {
-            let parts: Vec<&str> = path.split('\\').collect();
-
-            if parts.len() == 1 {
-                root_nodes.push(node);
-            } else {
-                let parent_path = parts[..parts.len() - 1].join("\\");
-
-                if all_nodes.contains_key(&parent_path) {
-                    all_nodes.insert(path, node);
+                if is_last {
+                    nodes[current].entry = Some(index);
+                    nodes[current].is_dir |= entry.is_dir;
                 } else {
-                    let mut found_parent = false;
-                    for root_node in &mut root_nodes {
-                        if add_child_to_node(root_node, &parent_path, node.clone()) {
-                            found_parent = true;
-                            break;
-                        }
-                    }
-                    if !found_parent {
-                        root_nodes.push(node);
-                    }
+                    nodes[current].is_dir = true;
                 }
             }
         }
-    }
 
-    while !all_nodes.is_empty() {
-        let mut processed_any = false;
-        let remaining_paths: Vec<String> = all_nodes.keys().cloned().collect();
-
-        for path in remaining_paths {
-            if let Some(node) = all_nodes.get(&path) {
-                let parts: Vec<&str> = path.split('\\').collect();
-                let parent_path = parts[..parts.len() - 1].join("\\");
-
-                let mut found_parent = false;
-                for root_node in &mut root_nodes {
-                    if add_child_to_node(root_node, &parent_path, node.clone()) {
-                        all_nodes.remove(&path);
-                        found_parent = true;
-                        processed_any = true;
-                        break;
-                    }
-                }
-
-                if !found_parent && parts.len() == 1 {
-                    if let Some(node) = all_nodes.remove(&path) {
-                        root_nodes.push(node);
-                        processed_any = true;
-                    }
-                }
+        // Children always come after their parent, so a reverse pass sees them first
+        for index in (1..nodes.len()).rev() {
+            let node = &mut nodes[index];
+            if !node.is_dir {
+                node.size = node
+                    .entry
+                    .map(|entry| entries[entry].size)
+                    .unwrap_or_default();
+                node.file_count = 1;
             }
+            let (size, file_count, parent) = (node.size, node.file_count, node.parent);
+            nodes[parent].size += size;
+            nodes[parent].file_count += file_count;
         }
 
-        if !processed_any {
-            break;
-        }
+        Self { entries, nodes }
     }
 
-    sort_nodes(&mut root_nodes);
-
-    if root_nodes.len() == 1 && root_nodes[0].children.is_some() {
-        root_nodes[0].expanded = true;
+    // Top-level nodes, with directories below `max_depth` levels left unloaded
+    pub fn root(&self, max_depth: Option<u32>) -> Vec<FileTreeNode> {
+        let mut roots = self.children(0, 0, max_depth);
+        if roots.len() == 1 && roots[0].children.is_some() {
+            roots[0].expanded = true;
+        }
+        roots
     }
 
-    root_nodes
-}
-
-fn add_child_to_node(node: &mut FileTreeNode, parent_path: &str, child: FileTreeNode) -> bool {
-    if node.path == parent_path {
-        if let Some(ref mut children) = node.children {
-            children.push(child);
-            return true;
+    // Contents of the directory at `path`, loaded `max_depth` levels deep
+    pub fn subtree(&self, path: &str, max_depth: Option<u32>) -> Result<Vec<FileTreeNode>> {
+        let mut current = 0;
+        let mut level = 0;
+        for part in path.split('\\').filter(|p| !p.is_empty()) {
+            current = *self.nodes[current]
+                .children
+                .get(part)
+                .ok_or(anyhow!("Directory not found in archive: {}", path))?;
+            level += 1;
         }
+        if !self.nodes[current].is_dir {
+            return Err(anyhow!("Not a directory: {}", path));
+        }
+        Ok(self.children(current, level, max_depth))
     }
 
-    if let Some(ref mut children) = node.children {
-        for child_node in children {
-            if add_child_to_node(child_node, parent_path, child.clone()) {
-                return true;
-            }
-        }
+    fn children(&self, index: usize, level: u32, max_depth: Option<u32>) -> Vec<FileTreeNode> {
+        let mut children: Vec<&usize> = self.nodes[index].children.values().collect();
+        children.sort_by(|&&a, &&b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name))
+        });
+        children
+            .into_iter()
+            .map(|&child| self.to_node(child, level, max_depth))
+            .collect()
     }
 
-    false
-}
+    fn to_node(&self, index: usize, level: u32, max_depth: Option<u32>) -> FileTreeNode {
+        let node = &self.nodes[index];
+        let entry = node.entry.map(|entry| &self.entries[entry]);
+        let truncated =
+            node.is_dir && !node.children.is_empty() && max_depth.is_some_and(|depth| depth <= 1);
+        let children = node.is_dir.then(|| {
+            if truncated {
+                Vec::new()
+            } else {
+                self.children(index, level + 1, max_depth.map(|depth| depth - 1))
+            }
+        });
 
-fn sort_nodes(nodes: &mut Vec<FileTreeNode>) {
-    nodes.sort_by(|a, b| {
-        if a.node_type != b.node_type {
-            return if a.node_type == "directory" {
-                std::cmp::Ordering::Less
+        FileTreeNode {
+            key: node.path.clone(),
+            name: node.name.clone(),
+            path: node.path.clone(),
+            node_type: if node.is_dir {
+                "directory".to_owned()
             } else {
-                std::cmp::Ordering::Greater
-            };
+                "file".to_owned()
+            },
+            children,
+            expanded: false,
+            level,
+            size: node.size,
+            file_count: node.file_count,
+            truncated,
+            modified: entry
+                .map(|entry| entry.modified.clone())
+                .unwrap_or_default(),
+            encrypted: entry.is_some_and(|entry| entry.encrypted),
         }
+    }
+}
 
-        a.name.cmp(&b.name)
-    });
+pub fn build_file_tree(entries: Vec<ArchiveEntry>) -> Vec<FileTreeNode> {
+    FileTree::new(entries).root(None)
+}
 
-    for node in nodes {
-        if let Some(ref mut children) = node.children {
-            if !children.is_empty() {
-                sort_nodes(children);
-            }
-        }
+async fn open_file_tree(path: &str, password: Option<&str>) -> Result<Arc<FileTree>> {
+    let password = match password {
+        Some(password) => password.to_owned(),
+        None => saved_password(path).await?,
+    };
+    if !is_archive_file(Path::new(path)) {
+        return Err(anyhow!("Unsupported archive format"));
+    }
+
+    let metadata = tokio::fs::metadata(path).await?;
+    let key = TreeKey {
+        path: path.to_owned(),
+        size: metadata.len(),
+        modified: metadata.modified()?,
+        password: password_fingerprint(&password),
+        unwrap_depth: Settings::read().await?.archive.unwrap_depth,
+    };
+    if let Some((cached_key, tree)) = LAST_TREE
+        .lock()
+        .map_err(|_| anyhow!("Failed to lock archive tree"))?
+        .as_ref()
+        && *cached_key == key
+    {
+        return Ok(tree.clone());
     }
+
+    let payload = unwrap_nested_archive(
+        &resolve_volume_path(Path::new(path))?,
+        Some(&password),
+        key.unwrap_depth,
+    )
+    .await?;
+    let tree = Arc::new(FileTree::new(payload.entries));
+    *LAST_TREE
+        .lock()
+        .map_err(|_| anyhow!("Failed to lock archive tree"))? = Some((key, tree.clone()));
+    Ok(tree)
+}
+
+// `max_depth` limits how many levels are returned, deeper directories come back `truncated`
+pub async fn get_archive_tree(
+    path: &str,
+    password: Option<&str>,
+    max_depth: Option<u32>,
+) -> Result<Vec<FileTreeNode>> {
+    Ok(open_file_tree(path, password).await?.root(max_depth))
 }
 
-pub async fn get_archive_tree(path: &str, password: Option<&str>) -> Result<Vec<FileTreeNode>> {
-    let file_paths = get_archive_content(path, password).await?;
-    Ok(build_file_tree(file_paths))
+// Loads the contents of a `truncated` directory
+pub async fn get_archive_subtree(
+    path: &str,
+    password: Option<&str>,
+    subtree: &str,
+    max_depth: Option<u32>,
+) -> Result<Vec<FileTreeNode>> {
+    open_file_tree(path, password)
+        .await?
+        .subtree(subtree, max_depth)
 }
@@ -1,8 +1,8 @@
+use super::pe::PeFile;
 use anyhow::{Result, anyhow};
 use base64::{Engine, engine::general_purpose::STANDARD};
 use dirs;
 use std::path::Path;
-use systemicons::get_icon;
 use tokio::fs;
 
 pub async fn convert_icon_to_base64(path: &str) -> Result<String> {
@@ -19,12 +19,15 @@ pub async fn convert_icon_to_base64(path: &str) -> Result<String> {
         .ok_or(anyhow!("Failed to get file extension"))?;
 
     match extension.as_str() {
+        // Every size the executable ships, so shortcuts stay sharp at any scale
         "exe" => {
-            let raw_icon = get_icon(&path.to_string_lossy(), 64)
-                .map_err(|e| anyhow!("Failed to extract icon from exe: {:?}", e))?;
+            let icon = PeFile::open(path)
+                .await?
+                .main_icon()?
+                .ok_or(anyhow!("Executable has no icon"))?;
             Ok(format!(
-                "data:image/png;base64,{}",
-                STANDARD.encode(&raw_icon)
+                "data:image/x-icon;base64,{}",
+                STANDARD.encode(icon.to_ico())
             ))
         }
         "ico" => {
@@ -76,18 +79,29 @@ pub async fn convert_base64_to_ico(base64_data: &str, filename: &str) -> Result<
     };
     let output_path = icons_dir.join(output_filename);
 
+    // Icons taken from executables already carry every size
+    if image_data.starts_with(&[0, 0, 1, 0]) {
+        fs::write(&output_path, &image_data).await?;
+        return Ok(output_path.to_string_lossy().to_string());
+    }
+
     tokio::task::spawn_blocking({
         let output_path = output_path.clone();
         move || -> Result<()> {
             let img = image::load_from_memory(&image_data)?;
-
-            let resized_img = img.resize(32, 32, image::imageops::FilterType::Lanczos3);
-
-            let rgba_img = resized_img.to_rgba8();
+            let source_size = img.width().max(img.height());
 
             let mut ico_dir = ico::IconDir::new(ico::ResourceType::Icon);
-            let ico_image = ico::IconImage::from_rgba_data(32, 32, rgba_img.into_raw());
-            ico_dir.add_entry(ico::IconDirEntry::encode(&ico_image)?);
+            for size in [16, 24, 32, 48, 64, 128, 256] {
+                if size > 32 && size > source_size {
+                    break;
+                }
+                let resized_img =
+                    img.resize_exact(size, size, image::imageops::FilterType::Lanczos3);
+                let ico_image =
+                    ico::IconImage::from_rgba_data(size, size, resized_img.to_rgba8().into_raw());
+                ico_dir.add_entry(ico::IconDirEntry::encode(&ico_image)?);
+            }
 
             let mut file = std::fs::File::create(&output_path)?;
             ico_dir.write(&mut file)?;
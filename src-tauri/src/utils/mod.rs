@@ -2,6 +2,7 @@ pub mod crypto;
 pub mod download;
 pub mod icon;
 pub mod path;
+pub mod pe;
 pub mod registry;
 pub mod shortcuts;
 
@@ -9,5 +10,6 @@ pub use crypto::*;
 pub use download::*;
 pub use icon::*;
 pub use path::*;
+pub use pe::*;
 pub use registry::*;
 pub use shortcuts::*;
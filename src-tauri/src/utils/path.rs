@@ -1,8 +1,8 @@
 use anyhow::Result;
-use windows_registry::{CURRENT_USER, LOCAL_MACHINE};
+use windows_registry::{CURRENT_USER, Key, LOCAL_MACHINE};
 
-pub fn add_to_path(path_directory: &str, current_user_only: bool) -> Result<()> {
-    let (key, path_key) = if current_user_only {
+fn environment_key(current_user_only: bool) -> Result<(Key, &'static str)> {
+    Ok(if current_user_only {
         (CURRENT_USER.create("Environment")?, "Path")
     } else {
         (
@@ -10,7 +10,25 @@ pub fn add_to_path(path_directory: &str, current_user_only: bool) -> Result<()>
                 .create(r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment")?,
             "path",
         )
-    };
+    })
+}
+
+// Entries of the stored PATH as written, variables are not expanded
+pub fn path_entries(current_user_only: bool) -> Result<Vec<String>> {
+    let (key, path_key) = environment_key(current_user_only)?;
+    Ok(key
+        .get_string(path_key)
+        .unwrap_or_default()
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+// Returns whether the directory was added, `false` when it was already on PATH
+pub fn add_to_path(path_directory: &str, current_user_only: bool) -> Result<bool> {
+    let (key, path_key) = environment_key(current_user_only)?;
 
     let current_path = key.get_string(path_key)?;
 
@@ -20,9 +38,10 @@ pub fn add_to_path(path_directory: &str, current_user_only: bool) -> Result<()>
     {
         let new_path = format!("{current_path};{path_directory}");
         key.set_expand_string(path_key, new_path)?;
+        return Ok(true);
     }
 
-    Ok(())
+    Ok(false)
 }
 
 pub fn remove_from_path(path_to_remove: &str, current_user_only: bool) -> Result<()> {
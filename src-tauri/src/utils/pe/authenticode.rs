@@ -0,0 +1,541 @@
+use super::{PeFile, read_u16, read_u32};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use ts_rs::TS;
+
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 2;
+
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+const OID_COUNTER_SIGNATURE: &str = "1.2.840.113549.1.9.6";
+const OID_RFC3161_TIMESTAMP: &str = "1.3.6.1.4.1.311.3.3.1";
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_CONTEXT_0: u8 = 0xA0;
+const TAG_CONTEXT_1: u8 = 0xA1;
+
+// What the Authenticode blob claims. Neither the messageDigest attribute nor the signer's
+// signature over the signed attributes is verified, so a copied certificate chain with a freshly
+// computed digest reads the same as a genuine signature. Never treat this as proof of origin.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct SignatureInfo {
+    pub signer_subject: String,
+    // Issuer names from the signer's issuer up to the root
+    pub issuer_chain: Vec<String>,
+    // RFC 3339, from the signed attributes or the countersignature
+    pub signing_time: Option<String>,
+    pub digest_algorithm: String,
+    // None when the digest algorithm is not supported
+    pub digest_matches: Option<bool>,
+}
+
+impl PeFile {
+    pub fn signature(&self) -> Result<Option<SignatureInfo>> {
+        let Some(table) = self.certificate_table() else {
+            return Ok(None);
+        };
+        // WIN_CERTIFICATE: length, revision, type, then the PKCS#7 blob
+        let length = read_u32(table, 0)? as usize;
+        if read_u16(table, 6)? != WIN_CERT_TYPE_PKCS_SIGNED_DATA {
+            return Err(anyhow!("Unsupported certificate type"));
+        }
+        let blob = table
+            .get(8..length)
+            .ok_or(anyhow!("Certificate table is truncated"))?;
+
+        let mut info = parse_signed_data(blob)?;
+        info.digest_matches = self
+            .authenticode_digest(&info.digest_algorithm)
+            .map(|digest| Some(digest) == embedded_digest(blob).ok());
+        Ok(Some(info))
+    }
+
+    // The image hashed without its checksum, its security directory entry and the certificates.
+    // For files with no gaps between sections this is what the section-ordered algorithm gives.
+    fn authenticode_digest(&self, algorithm: &str) -> Option<Vec<u8>> {
+        let data = self.data();
+        let pe_offset = read_u32(data, 60).ok()? as usize;
+        let optional_header = pe_offset + 24;
+        let checksum = optional_header + 64;
+        let security_entry = optional_header + if self.is_64 { 112 } else { 96 } + 4 * 8;
+        let certificates = read_u32(data, security_entry).ok()? as usize;
+        let certificates_end = certificates + read_u32(data, security_entry + 4).ok()? as usize;
+
+        let ranges = [
+            data.get(..checksum)?,
+            data.get(checksum + 4..security_entry)?,
+            data.get(security_entry + 8..certificates)?,
+            data.get(certificates_end.min(data.len())..)?,
+        ];
+        fn hash<D: Digest>(ranges: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for range in ranges {
+                hasher.update(range);
+            }
+            hasher.finalize().to_vec()
+        }
+        match algorithm {
+            "sha1" => Some(hash::<Sha1>(&ranges)),
+            "sha256" => Some(hash::<Sha256>(&ranges)),
+            "sha384" => Some(hash::<Sha384>(&ranges)),
+            "sha512" => Some(hash::<Sha512>(&ranges)),
+            _ => None,
+        }
+    }
+}
+
+fn parse_signed_data(blob: &[u8]) -> Result<SignatureInfo> {
+    let signed_data = signed_data_of(blob)?;
+    let fields = signed_data.children()?;
+    let certificates: Vec<Der> = fields
+        .iter()
+        .find(|field| field.tag == TAG_CONTEXT_0)
+        .map(Der::children)
+        .transpose()?
+        .unwrap_or_default();
+    let signer_info = fields
+        .last()
+        .filter(|field| field.tag == TAG_SET)
+        .ok_or(anyhow!("Missing signer infos"))?
+        .children()?
+        .into_iter()
+        .next()
+        .ok_or(anyhow!("No signer info"))?;
+
+    // SignerInfo: version, issuerAndSerialNumber, digestAlgorithm, [0] signedAttrs, ...
+    let signer_fields = signer_info.children()?;
+    let issuer_and_serial = signer_fields
+        .get(1)
+        .ok_or(anyhow!("Missing signer identifier"))?
+        .children()?;
+    let (issuer, serial) = match issuer_and_serial.as_slice() {
+        [issuer, serial, ..] => (issuer.raw, serial.contents),
+        _ => return Err(anyhow!("Malformed signer identifier")),
+    };
+    let digest_algorithm = signer_fields
+        .get(2)
+        .ok_or(anyhow!("Missing digest algorithm"))?
+        .children()?
+        .first()
+        .ok_or(anyhow!("Missing digest algorithm"))?
+        .oid()?;
+
+    let parsed: Vec<Certificate> = certificates
+        .iter()
+        .filter_map(|certificate| Certificate::parse(certificate).ok())
+        .collect();
+    let signer = parsed
+        .iter()
+        .find(|certificate| certificate.issuer == issuer && certificate.serial == serial);
+
+    let mut issuer_chain = Vec::new();
+    let mut next_issuer = signer
+        .map(|certificate| certificate.issuer)
+        .unwrap_or(issuer);
+    // Bounded in case certificates issue each other in a loop
+    for _ in 0..parsed.len() + 1 {
+        issuer_chain.push(name_to_string(next_issuer)?);
+        match parsed
+            .iter()
+            .find(|certificate| certificate.subject == next_issuer)
+        {
+            Some(certificate) if certificate.issuer != certificate.subject => {
+                next_issuer = certificate.issuer
+            }
+            _ => break,
+        }
+    }
+
+    Ok(SignatureInfo {
+        signer_subject: match signer {
+            Some(certificate) => name_to_string(certificate.subject)?,
+            None => String::new(),
+        },
+        issuer_chain,
+        signing_time: signing_time(&signer_fields)?,
+        digest_algorithm: digest_name(&digest_algorithm).to_owned(),
+        digest_matches: None,
+    })
+}
+
+// ContentInfo { contentType signedData, [0] SignedData }
+fn signed_data_of(blob: &[u8]) -> Result<Der<'_>> {
+    let (content_info, _) = Der::read(blob)?;
+    let fields = content_info.children()?;
+    match fields.as_slice() {
+        [content_type, content, ..]
+            if content_type.oid()? == OID_SIGNED_DATA && content.tag == TAG_CONTEXT_0 =>
+        {
+            content
+                .children()?
+                .into_iter()
+                .next()
+                .ok_or(anyhow!("Empty SignedData"))
+        }
+        _ => Err(anyhow!("Not a PKCS#7 SignedData blob")),
+    }
+}
+
+// SpcIndirectDataContent { data, messageDigest { digestAlgorithm, digest } }
+fn embedded_digest(blob: &[u8]) -> Result<Vec<u8>> {
+    let signed_data = signed_data_of(blob)?;
+    let encapsulated = signed_data
+        .children()?
+        .into_iter()
+        .find(|field| field.tag == TAG_SEQUENCE)
+        .ok_or(anyhow!("Missing encapsulated content"))?;
+    let content = encapsulated
+        .children()?
+        .into_iter()
+        .find(|field| field.tag == TAG_CONTEXT_0)
+        .ok_or(anyhow!("Missing indirect data"))?
+        .children()?
+        .into_iter()
+        .next()
+        .ok_or(anyhow!("Missing indirect data"))?;
+    let message_digest = content
+        .children()?
+        .into_iter()
+        .nth(1)
+        .ok_or(anyhow!("Missing message digest"))?;
+    let digest = message_digest
+        .children()?
+        .into_iter()
+        .find(|field| field.tag == TAG_OCTET_STRING)
+        .ok_or(anyhow!("Missing digest value"))?;
+    Ok(digest.contents.to_vec())
+}
+
+// Signed attributes first, then a PKCS#9 countersignature, then an RFC 3161 timestamp token
+fn signing_time(signer_fields: &[Der]) -> Result<Option<String>> {
+    let attributes = |tag: u8| -> Result<Vec<(String, Vec<Der>)>> {
+        let Some(set) = signer_fields.iter().find(|field| field.tag == tag) else {
+            return Ok(Vec::new());
+        };
+        set.children()?
+            .iter()
+            .map(|attribute| {
+                let parts = attribute.children()?;
+                let oid = parts.first().ok_or(anyhow!("Empty attribute"))?.oid()?;
+                let values = match parts.get(1) {
+                    Some(values) => values.children()?,
+                    None => Vec::new(),
+                };
+                Ok((oid, values))
+            })
+            .collect()
+    };
+
+    for (oid, values) in attributes(TAG_CONTEXT_0)? {
+        if oid == OID_SIGNING_TIME
+            && let Some(time) = values.first()
+        {
+            return Ok(Some(time.time()?));
+        }
+    }
+
+    // Countersignatures are unsigned attributes anyone can append, a broken one only loses the time
+    for (oid, values) in attributes(TAG_CONTEXT_1).unwrap_or_default() {
+        let Some(value) = values.first() else {
+            continue;
+        };
+        let time = if oid == OID_COUNTER_SIGNATURE {
+            value.children().and_then(|fields| signing_time(&fields))
+        } else if oid == OID_RFC3161_TIMESTAMP {
+            timestamp_time(value)
+        } else {
+            continue;
+        };
+        if let Ok(Some(time)) = time {
+            return Ok(Some(time));
+        }
+    }
+
+    Ok(None)
+}
+
+// The token's content is a TSTInfo whose fifth field is genTime
+fn timestamp_time(token: &Der) -> Result<Option<String>> {
+    let token = signed_data_of(token.raw)?;
+    let encapsulated = token
+        .children()?
+        .into_iter()
+        .find(|field| field.tag == TAG_SEQUENCE)
+        .ok_or(anyhow!("Missing timestamp content"))?;
+    let Some(wrapper) = encapsulated
+        .children()?
+        .into_iter()
+        .find(|field| field.tag == TAG_CONTEXT_0)
+    else {
+        return Ok(None);
+    };
+    let Some(octets) = wrapper.children()?.into_iter().next() else {
+        return Ok(None);
+    };
+    let (tst_info, _) = Der::read(octets.contents)?;
+    tst_info
+        .children()?
+        .into_iter()
+        .find(|field| field.tag == TAG_GENERALIZED_TIME)
+        .map(|time| time.time())
+        .transpose()
+}
+
+struct Certificate<'a> {
+    serial: &'a [u8],
+    issuer: &'a [u8],
+    subject: &'a [u8],
+}
+
+impl<'a> Certificate<'a> {
+    // TBSCertificate: [0] version, serial, signature, issuer, validity, subject, ...
+    fn parse(certificate: &Der<'a>) -> Result<Self> {
+        let tbs = certificate
+            .children()?
+            .into_iter()
+            .next()
+            .ok_or(anyhow!("Empty certificate"))?;
+        let fields: Vec<Der> = tbs
+            .children()?
+            .into_iter()
+            .skip_while(|field| field.tag == TAG_CONTEXT_0)
+            .collect();
+        match fields.as_slice() {
+            [serial, _, issuer, _, subject, ..] if serial.tag == TAG_INTEGER => Ok(Self {
+                serial: serial.contents,
+                issuer: issuer.raw,
+                subject: subject.raw,
+            }),
+            _ => Err(anyhow!("Malformed certificate")),
+        }
+    }
+}
+
+// `CN=Contoso Ltd, O=Contoso Ltd, C=US`, most specific part first like Windows shows it
+fn name_to_string(name: &[u8]) -> Result<String> {
+    let (name, _) = Der::read(name)?;
+    let mut parts = Vec::new();
+    for rdn in name.children()? {
+        for attribute in rdn.children()? {
+            let fields = attribute.children()?;
+            let (Some(oid), Some(value)) = (fields.first(), fields.get(1)) else {
+                continue;
+            };
+            let oid = oid.oid()?;
+            let key = match oid.as_str() {
+                "2.5.4.3" => "CN",
+                "2.5.4.6" => "C",
+                "2.5.4.7" => "L",
+                "2.5.4.8" => "S",
+                "2.5.4.10" => "O",
+                "2.5.4.11" => "OU",
+                "1.2.840.113549.1.9.1" => "E",
+                other => other,
+            };
+            parts.push(format!("{key}={}", value.text()));
+        }
+    }
+    parts.reverse();
+    Ok(parts.join(", "))
+}
+
+fn digest_name(oid: &str) -> &str {
+    match oid {
+        "1.3.14.3.2.26" => "sha1",
+        "2.16.840.1.101.3.4.2.1" => "sha256",
+        "2.16.840.1.101.3.4.2.2" => "sha384",
+        "2.16.840.1.101.3.4.2.3" => "sha512",
+        "1.2.840.113549.2.5" => "md5",
+        other => other,
+    }
+}
+
+// A DER element: tag, contents, and the whole encoding for byte-wise comparison
+#[derive(Debug, Clone, Copy)]
+struct Der<'a> {
+    tag: u8,
+    contents: &'a [u8],
+    raw: &'a [u8],
+}
+
+impl<'a> Der<'a> {
+    fn read(data: &'a [u8]) -> Result<(Self, &'a [u8])> {
+        let truncated = || anyhow!("Truncated DER element");
+        let tag = *data.first().ok_or_else(truncated)?;
+        let first = *data.get(1).ok_or_else(truncated)?;
+        let (length, header) = match first {
+            0x00..=0x7F => (usize::from(first), 2),
+            0x81..=0x84 => {
+                let count = usize::from(first & 0x7F);
+                let bytes = data.get(2..2 + count).ok_or_else(truncated)?;
+                let length = bytes
+                    .iter()
+                    .fold(0usize, |length, byte| (length << 8) | usize::from(*byte));
+                (length, 2 + count)
+            }
+            _ => return Err(anyhow!("Unsupported DER length")),
+        };
+        let end = header + length;
+        let raw = data.get(..end).ok_or_else(truncated)?;
+        Ok((
+            Self {
+                tag,
+                contents: &raw[header..],
+                raw,
+            },
+            &data[end..],
+        ))
+    }
+
+    fn children(&self) -> Result<Vec<Der<'a>>> {
+        let mut children = Vec::new();
+        let mut rest = self.contents;
+        while !rest.is_empty() {
+            let (child, next) = Der::read(rest)?;
+            children.push(child);
+            rest = next;
+        }
+        Ok(children)
+    }
+
+    fn oid(&self) -> Result<String> {
+        if self.tag != TAG_OID || self.contents.is_empty() {
+            return Err(anyhow!("Expected an object identifier"));
+        }
+        let mut arcs = vec![
+            u64::from(self.contents[0] / 40),
+            u64::from(self.contents[0] % 40),
+        ];
+        let mut value = 0u64;
+        for byte in &self.contents[1..] {
+            value = (value << 7) | u64::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                arcs.push(value);
+                value = 0;
+            }
+        }
+        Ok(arcs
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join("."))
+    }
+
+    fn text(&self) -> String {
+        match self.tag {
+            // BMPString
+            0x1E => String::from_utf16_lossy(
+                &self
+                    .contents
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                    .collect::<Vec<_>>(),
+            ),
+            // T61String, treated as Latin-1
+            0x14 => self.contents.iter().map(|byte| char::from(*byte)).collect(),
+            _ => String::from_utf8_lossy(self.contents).to_string(),
+        }
+    }
+
+    fn time(&self) -> Result<String> {
+        let text = String::from_utf8_lossy(self.contents);
+        let text = text.trim_end_matches('Z');
+        let time = match self.tag {
+            TAG_UTC_TIME => {
+                // Two-digit years below 50 are in the 2000s
+                let century = if text.get(..2).is_some_and(|year| year < "50") {
+                    "20"
+                } else {
+                    "19"
+                };
+                chrono::NaiveDateTime::parse_from_str(&format!("{century}{text}"), "%Y%m%d%H%M%S")?
+            }
+            TAG_GENERALIZED_TIME => chrono::NaiveDateTime::parse_from_str(
+                text.split('.').next().unwrap_or_default(),
+                "%Y%m%d%H%M%S",
+            )?,
+            _ => return Err(anyhow!("Expected a time")),
+        };
+        Ok(time.and_utc().to_rfc3339())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SIGNED_FIXTURE;
+    use super::*;
+
+    const ROOT: &str = "CN=AppPorter Test Root, O=AppPorter Fixtures, C=US";
+    const SIGNER: &str = "CN=AppPorter Test Signer, O=AppPorter Fixtures, C=US";
+
+    fn fixture_blob() -> Vec<u8> {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        let table = pe.certificate_table().unwrap();
+        let length = read_u32(table, 0).unwrap() as usize;
+        table[8..length].to_vec()
+    }
+
+    #[test]
+    fn reads_the_signature() {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        let info = pe.signature().unwrap().unwrap();
+        assert_eq!(info.signer_subject, SIGNER);
+        assert_eq!(info.issuer_chain, [ROOT]);
+        assert_eq!(
+            info.signing_time.as_deref(),
+            Some("2024-03-15T12:30:00+00:00")
+        );
+        assert_eq!(info.digest_algorithm, "sha256");
+        assert_eq!(info.digest_matches, Some(true));
+    }
+
+    #[test]
+    fn detects_a_modified_image() {
+        let mut data = SIGNED_FIXTURE.to_vec();
+        // A byte of the DOS stub, covered by the digest
+        data[0x50] ^= 0xFF;
+        let pe = PeFile::parse(data).unwrap();
+        assert_eq!(pe.signature().unwrap().unwrap().digest_matches, Some(false));
+    }
+
+    #[test]
+    fn rejects_truncated_der() {
+        let blob = fixture_blob();
+        assert!(parse_signed_data(&blob).is_ok());
+        for length in 0..blob.len() {
+            assert!(parse_signed_data(&blob[..length]).is_err());
+        }
+        assert!(Der::read(&[TAG_SEQUENCE]).is_err());
+        assert!(Der::read(&[TAG_SEQUENCE, 0x82, 0x01]).is_err());
+        assert!(Der::read(&[TAG_SEQUENCE, 0x05, 0x00]).is_err());
+    }
+
+    #[test]
+    fn ignores_a_malformed_timestamp() {
+        // [1] { SEQUENCE { OID 1.3.6.1.4.1.311.3.3.1, SET { SEQUENCE { INTEGER 5 } } } }
+        let unsigned_attributes = [
+            0xA1, 0x15, 0x30, 0x13, 0x06, 0x0A, 0x2B, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x03,
+            0x03, 0x01, 0x31, 0x05, 0x30, 0x03, 0x02, 0x01, 0x05,
+        ];
+        let (field, _) = Der::read(&unsigned_attributes).unwrap();
+        assert_eq!(signing_time(&[field]).unwrap(), None);
+    }
+
+    #[test]
+    fn unsigned_images_have_no_signature() {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        let (offset, _) = pe.data_directory(4).unwrap();
+        let pe = PeFile::parse(SIGNED_FIXTURE[..offset as usize].to_vec()).unwrap();
+        assert!(pe.signature().unwrap().is_none());
+    }
+}
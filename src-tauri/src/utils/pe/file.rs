@@ -0,0 +1,219 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ts_rs::TS;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PeSubsystem {
+    Gui,
+    Console,
+    Native,
+    Other,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PeMachine {
+    X86,
+    X64,
+    Arm,
+    Arm64,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeSection {
+    pub name: String,
+    pub virtual_address: u32,
+    pub virtual_size: u32,
+    pub raw_offset: u32,
+    pub raw_size: u32,
+}
+
+const SECURITY_DIRECTORY: usize = 4;
+const COM_DESCRIPTOR_DIRECTORY: usize = 14;
+
+// The parts of a PE image the rest of the code needs, parsed without mapping the image
+#[derive(Debug, Clone)]
+pub struct PeFile {
+    data: Vec<u8>,
+    optional_header: usize,
+    pub is_64: bool,
+    pub machine: PeMachine,
+    pub subsystem: PeSubsystem,
+    pub sections: Vec<PeSection>,
+}
+
+impl PeSubsystem {
+    fn from_raw(value: u16) -> Self {
+        match value {
+            1 => Self::Native,
+            2 => Self::Gui,
+            3 => Self::Console,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl PeMachine {
+    fn from_raw(value: u16) -> Self {
+        match value {
+            0x014C => Self::X86,
+            0x8664 => Self::X64,
+            0x01C0 | 0x01C4 => Self::Arm,
+            0xAA64 => Self::Arm64,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl PeFile {
+    pub fn parse(data: Vec<u8>) -> Result<Self> {
+        if data.get(..2) != Some(b"MZ") {
+            return Err(anyhow!("Not a PE file"));
+        }
+        let pe_offset = read_u32(&data, 60)? as usize;
+        if data.get(pe_offset..pe_offset + 4) != Some(b"PE\0\0") {
+            return Err(anyhow!("Not a PE file"));
+        }
+        let machine = PeMachine::from_raw(read_u16(&data, pe_offset + 4)?);
+        let section_count = read_u16(&data, pe_offset + 6)?;
+        let optional_header_size = read_u16(&data, pe_offset + 20)?;
+        let optional_header = pe_offset + 24;
+        let is_64 = read_u16(&data, optional_header)? == 0x20B;
+        // Subsystem sits at the same offset in PE32 and PE32+ optional headers
+        let subsystem = PeSubsystem::from_raw(read_u16(&data, optional_header + 68)?);
+
+        let section_table = optional_header + usize::from(optional_header_size);
+        let sections = (0..usize::from(section_count))
+            .map(|index| {
+                let offset = section_table + index * 40;
+                let name = data
+                    .get(offset..offset + 8)
+                    .ok_or(anyhow!("Unexpected end of PE file"))?;
+                Ok(PeSection {
+                    name: String::from_utf8_lossy(name)
+                        .trim_end_matches('\0')
+                        .to_owned(),
+                    virtual_size: read_u32(&data, offset + 8)?,
+                    virtual_address: read_u32(&data, offset + 12)?,
+                    raw_size: read_u32(&data, offset + 16)?,
+                    raw_offset: read_u32(&data, offset + 20)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            data,
+            optional_header,
+            is_64,
+            machine,
+            subsystem,
+            sections,
+        })
+    }
+
+    pub async fn open(path: &Path) -> Result<Self> {
+        Self::parse(tokio::fs::read(path).await?)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    // RVA and size of an entry in the optional header's data directory
+    pub fn data_directory(&self, index: usize) -> Option<(u32, u32)> {
+        let base = self.optional_header + if self.is_64 { 108 } else { 92 };
+        let count = read_u32(&self.data, base).ok()? as usize;
+        if index >= count {
+            return None;
+        }
+        let offset = base + 4 + index * 8;
+        let rva = read_u32(&self.data, offset).ok()?;
+        let size = read_u32(&self.data, offset + 4).ok()?;
+        (rva != 0 && size != 0).then_some((rva, size))
+    }
+
+    // .NET assemblies carry a COM descriptor directory
+    pub fn has_clr(&self) -> bool {
+        self.data_directory(COM_DESCRIPTOR_DIRECTORY).is_some()
+    }
+
+    // The security directory holds the Authenticode blob, addressed by file offset rather than RVA
+    pub fn certificate_table(&self) -> Option<&[u8]> {
+        let (offset, size) = self.data_directory(SECURITY_DIRECTORY)?;
+        self.data
+            .get(offset as usize..offset as usize + size as usize)
+    }
+
+    // None when no section maps the RVA or the mapped offset lies past the end of the file
+    pub fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        let section = self.sections.iter().find(|section| {
+            let size = section.virtual_size.max(section.raw_size);
+            rva >= section.virtual_address && rva - section.virtual_address < size
+        })?;
+        let offset =
+            (section.raw_offset as usize).checked_add((rva - section.virtual_address) as usize)?;
+        (offset < self.data.len()).then_some(offset)
+    }
+
+    pub fn contains(&self, needle: &[u8]) -> bool {
+        !needle.is_empty()
+            && self
+                .data
+                .windows(needle.len())
+                .any(|window| window == needle)
+    }
+}
+
+pub fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(anyhow!("Unexpected end of PE file"))
+}
+
+pub fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or(anyhow!("Unexpected end of PE file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SIGNED_FIXTURE;
+    use super::*;
+
+    #[test]
+    fn maps_rvas_into_sections() {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        for section in &pe.sections {
+            assert_eq!(
+                pe.rva_to_offset(section.virtual_address),
+                Some(section.raw_offset as usize)
+            );
+        }
+        assert_eq!(pe.rva_to_offset(0), None);
+    }
+
+    #[test]
+    fn rejects_offsets_outside_the_file() {
+        let mut pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        pe.sections = vec![PeSection {
+            name: ".evil".to_owned(),
+            virtual_address: 0x1000,
+            virtual_size: u32::MAX - 0x1000,
+            raw_offset: u32::MAX - 0x10,
+            raw_size: 0x200,
+        }];
+        // Would wrap around in u32
+        assert_eq!(pe.rva_to_offset(0x2000), None);
+        assert_eq!(pe.rva_to_offset(u32::MAX - 1), None);
+
+        pe.sections[0].raw_offset = 0x200;
+        assert_eq!(pe.rva_to_offset(0x1010), Some(0x210));
+        assert_eq!(pe.rva_to_offset(0x1000 + SIGNED_FIXTURE.len() as u32), None);
+    }
+}
@@ -0,0 +1,172 @@
+use super::{PeFile, RT_GROUP_ICON, RT_ICON, ResourceId, read_u16};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::io::Cursor;
+
+// One image of an icon group, stored as a PNG or as a headerless BMP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeIcon {
+    pub width: u32,
+    pub height: u32,
+    pub color_count: u8,
+    pub planes: u16,
+    pub bit_count: u16,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeIconGroup {
+    pub name: ResourceId,
+    pub icons: Vec<PeIcon>,
+}
+
+impl PeFile {
+    // Icon groups in resource order, the first one is what Explorer shows for the file
+    pub fn icon_groups(&self) -> Result<Vec<PeIconGroup>> {
+        let resources = self.resources()?;
+        let mut images: HashMap<u16, &[u8]> = HashMap::new();
+        for resource in &resources {
+            if resource.kind == ResourceId::Id(RT_ICON)
+                && let ResourceId::Id(id) = resource.name
+            {
+                images.entry(id).or_insert(resource.data);
+            }
+        }
+
+        let mut groups: Vec<PeIconGroup> = Vec::new();
+        for resource in resources
+            .iter()
+            .filter(|resource| resource.kind == ResourceId::Id(RT_GROUP_ICON))
+        {
+            // Localized copies of a group share its name
+            if groups.iter().any(|group| group.name == resource.name) {
+                continue;
+            }
+            let count = usize::from(read_u16(resource.data, 4)?);
+            let icons = (0..count)
+                .filter_map(|index| {
+                    let entry = 6 + index * 14;
+                    let header = resource.data.get(entry..entry + 14)?;
+                    let id = read_u16(header, 12).ok()?;
+                    Some(PeIcon {
+                        width: icon_dimension(header[0]),
+                        height: icon_dimension(header[1]),
+                        color_count: header[2],
+                        planes: read_u16(header, 4).ok()?,
+                        bit_count: read_u16(header, 6).ok()?,
+                        data: images.get(&id)?.to_vec(),
+                    })
+                })
+                .collect();
+            groups.push(PeIconGroup {
+                name: resource.name.clone(),
+                icons,
+            });
+        }
+        Ok(groups)
+    }
+
+    pub fn main_icon(&self) -> Result<Option<PeIconGroup>> {
+        Ok(self
+            .icon_groups()?
+            .into_iter()
+            .find(|group| !group.icons.is_empty()))
+    }
+}
+
+impl PeIcon {
+    pub fn is_png(&self) -> bool {
+        self.data.starts_with(b"\x89PNG")
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        if self.is_png() {
+            return Ok(self.data.clone());
+        }
+        let ico = PeIconGroup {
+            name: ResourceId::Id(0),
+            icons: vec![self.clone()],
+        }
+        .to_ico();
+        let dir = ico::IconDir::read(Cursor::new(ico))?;
+        let image = dir
+            .entries()
+            .first()
+            .ok_or(anyhow!("Icon has no image"))?
+            .decode()?;
+        let mut png = Vec::new();
+        image.write_png(&mut png)?;
+        Ok(png)
+    }
+}
+
+impl PeIconGroup {
+    // Smallest image covering `size` pixels, deepest colors first, else the largest one
+    pub fn frame_for(&self, size: u32) -> Option<&PeIcon> {
+        let by_quality = |icon: &&PeIcon| (icon.width, icon.bit_count);
+        self.icons
+            .iter()
+            .filter(|icon| icon.width >= size)
+            .min_by_key(|icon| (icon.width, u16::MAX - icon.bit_count))
+            .or_else(|| self.icons.iter().max_by_key(by_quality))
+    }
+
+    // A complete `.ico` file with every image of the group
+    pub fn to_ico(&self) -> Vec<u8> {
+        let mut ico = Vec::new();
+        ico.extend_from_slice(&0u16.to_le_bytes());
+        ico.extend_from_slice(&1u16.to_le_bytes());
+        ico.extend_from_slice(&(self.icons.len() as u16).to_le_bytes());
+
+        let mut offset = 6 + 16 * self.icons.len() as u32;
+        for icon in &self.icons {
+            // 256 is stored as 0
+            ico.push(icon.width as u8);
+            ico.push(icon.height as u8);
+            ico.push(icon.color_count);
+            ico.push(0);
+            ico.extend_from_slice(&icon.planes.to_le_bytes());
+            ico.extend_from_slice(&icon.bit_count.to_le_bytes());
+            ico.extend_from_slice(&(icon.data.len() as u32).to_le_bytes());
+            ico.extend_from_slice(&offset.to_le_bytes());
+            offset += icon.data.len() as u32;
+        }
+        for icon in &self.icons {
+            ico.extend_from_slice(&icon.data);
+        }
+        ico
+    }
+}
+
+// Directory entries store 256 as 0
+fn icon_dimension(value: u8) -> u32 {
+    if value == 0 { 256 } else { u32::from(value) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SIGNED_FIXTURE;
+    use super::*;
+
+    #[test]
+    fn extracts_the_icon_group() {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        let groups = pe.icon_groups().unwrap();
+        assert_eq!(groups.len(), 1);
+
+        let group = pe.main_icon().unwrap().unwrap();
+        assert_eq!(group.name, ResourceId::Id(1));
+        assert_eq!(group.icons.len(), 1);
+        let icon = &group.icons[0];
+        assert_eq!((icon.width, icon.height, icon.bit_count), (16, 16, 32));
+        assert!(!icon.is_png());
+
+        // Nothing covers 64 pixels, so the largest image is used
+        assert_eq!(group.frame_for(64), Some(icon));
+        assert!(icon.to_png().unwrap().starts_with(b"\x89PNG"));
+
+        let ico = group.to_ico();
+        assert_eq!(&ico[..6], &[0, 0, 1, 0, 1, 0]);
+        assert_eq!(ico.len(), 6 + 16 + icon.data.len());
+    }
+}
@@ -0,0 +1,108 @@
+use super::{PeFile, read_u32};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+const IMPORT_DIRECTORY: usize = 1;
+const DELAY_IMPORT_DIRECTORY: usize = 13;
+
+// DLL name prefixes of runtimes that have to be installed separately
+const NOTABLE_RUNTIMES: [(&str, &str); 17] = [
+    ("vcruntime140", "Visual C++ 2015-2022"),
+    ("msvcp140", "Visual C++ 2015-2022"),
+    ("concrt140", "Visual C++ 2015-2022"),
+    ("vccorlib140", "Visual C++ 2015-2022"),
+    ("msvcr120", "Visual C++ 2013"),
+    ("msvcp120", "Visual C++ 2013"),
+    ("msvcr110", "Visual C++ 2012"),
+    ("msvcp110", "Visual C++ 2012"),
+    ("msvcr100", "Visual C++ 2010"),
+    ("msvcp100", "Visual C++ 2010"),
+    ("msvcr90", "Visual C++ 2008"),
+    ("msvcp90", "Visual C++ 2008"),
+    ("msvcr80", "Visual C++ 2005"),
+    ("msvcp80", "Visual C++ 2005"),
+    ("ucrtbase", "Universal C Runtime"),
+    ("api-ms-win-crt-", "Universal C Runtime"),
+    ("mscoree", ".NET Framework"),
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct RuntimeDependency {
+    pub runtime: String,
+    pub dlls: Vec<String>,
+}
+
+impl PeFile {
+    // Names of every imported DLL, delay-loaded ones included, in lowercase
+    pub fn imports(&self) -> Result<Vec<String>> {
+        let mut dlls = Vec::new();
+
+        if let Some((rva, _)) = self.data_directory(IMPORT_DIRECTORY)
+            && let Some(mut descriptor) = self.rva_to_offset(rva)
+        {
+            // IMAGE_IMPORT_DESCRIPTOR, terminated by an all-zero entry
+            while let Ok(name_rva) = read_u32(self.data(), descriptor + 12)
+                && name_rva != 0
+            {
+                if let Some(name) = self.read_c_string(name_rva)
+                    && !dlls.contains(&name)
+                {
+                    dlls.push(name);
+                }
+                descriptor += 20;
+            }
+        }
+
+        if let Some((rva, _)) = self.data_directory(DELAY_IMPORT_DIRECTORY)
+            && let Some(mut descriptor) = self.rva_to_offset(rva)
+        {
+            // ImgDelayDescr; old linkers stored virtual addresses, which are skipped
+            while let Ok(name_rva) = read_u32(self.data(), descriptor + 4)
+                && name_rva != 0
+            {
+                let uses_rva = read_u32(self.data(), descriptor)? & 1 != 0;
+                if uses_rva
+                    && let Some(name) = self.read_c_string(name_rva)
+                    && !dlls.contains(&name)
+                {
+                    dlls.push(name);
+                }
+                descriptor += 32;
+            }
+        }
+
+        Ok(dlls)
+    }
+
+    pub fn runtime_dependencies(&self) -> Result<Vec<RuntimeDependency>> {
+        let mut dependencies: Vec<RuntimeDependency> = Vec::new();
+        for dll in self.imports()? {
+            let Some((_, runtime)) = NOTABLE_RUNTIMES
+                .iter()
+                .find(|(prefix, _)| dll.starts_with(prefix))
+            else {
+                continue;
+            };
+            match dependencies
+                .iter_mut()
+                .find(|dependency| dependency.runtime == *runtime)
+            {
+                Some(dependency) => dependency.dlls.push(dll),
+                None => dependencies.push(RuntimeDependency {
+                    runtime: runtime.to_string(),
+                    dlls: vec![dll],
+                }),
+            }
+        }
+        Ok(dependencies)
+    }
+
+    fn read_c_string(&self, rva: u32) -> Option<String> {
+        let start = self.rva_to_offset(rva)?;
+        let bytes = self.data().get(start..)?;
+        let end = bytes.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..end]).to_lowercase())
+    }
+}
@@ -0,0 +1,53 @@
+use super::{PeFile, RT_MANIFEST, ResourceId};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionLevel {
+    AsInvoker,
+    HighestAvailable,
+    RequireAdministrator,
+}
+
+impl PeFile {
+    // The embedded application manifest, the one with resource id 1 when there are several
+    pub fn manifest(&self) -> Result<Option<String>> {
+        let manifests = self.resources_of_type(RT_MANIFEST)?;
+        let manifest = manifests
+            .iter()
+            .find(|resource| resource.name == ResourceId::Id(1))
+            .or(manifests.first());
+        Ok(manifest.map(|resource| {
+            String::from_utf8_lossy(resource.data)
+                .trim_start_matches('\u{feff}')
+                .to_owned()
+        }))
+    }
+
+    pub fn execution_level(&self) -> Result<Option<ExecutionLevel>> {
+        Ok(self.manifest()?.as_deref().and_then(parse_execution_level))
+    }
+}
+
+// Pulls `level` out of `<requestedExecutionLevel level="..." />` without a full XML parser
+pub fn parse_execution_level(manifest: &str) -> Option<ExecutionLevel> {
+    const ELEMENT: &str = "requestedExecutionLevel";
+    let element = &manifest[manifest.find(ELEMENT)? + ELEMENT.len()..];
+    let element = &element[..element.find('>').unwrap_or(element.len())];
+    let value = element[element.find("level")? + "level".len()..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let level = value[1..].split(quote).next()?;
+
+    match level {
+        "asInvoker" => Some(ExecutionLevel::AsInvoker),
+        "highestAvailable" => Some(ExecutionLevel::HighestAvailable),
+        "requireAdministrator" => Some(ExecutionLevel::RequireAdministrator),
+        _ => None,
+    }
+}
@@ -0,0 +1,23 @@
+pub mod authenticode;
+pub mod file;
+pub mod icons;
+pub mod imports;
+pub mod manifest;
+pub mod resources;
+pub mod version_info;
+
+pub use authenticode::*;
+pub use file::*;
+pub use icons::*;
+pub use imports::*;
+pub use manifest::*;
+pub use resources::*;
+pub use version_info::*;
+
+// A 5 KB x64 stub with a version resource, one 16x16 icon and an Authenticode signature by a
+// throwaway test CA, signed at 2024-03-15 12:30 UTC
+#[cfg(test)]
+pub(crate) const SIGNED_FIXTURE: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/pe/signed.exe"
+));
@@ -0,0 +1,198 @@
+use super::{PeFile, read_u16, read_u32};
+use anyhow::{Result, anyhow};
+use std::collections::HashSet;
+
+pub const RT_ICON: u16 = 3;
+pub const RT_GROUP_ICON: u16 = 14;
+pub const RT_VERSION: u16 = 16;
+pub const RT_MANIFEST: u16 = 24;
+
+const RESOURCE_DIRECTORY: usize = 2;
+
+// Crafted trees can overlap directories or point them back at each other, so the walk is bounded.
+// Real executables stay far below these.
+const MAX_DIRECTORY_ENTRIES: usize = 16 * 1024;
+const MAX_RESOURCES: usize = 4096;
+const MAX_NAME_LENGTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceId {
+    Id(u16),
+    Name(String),
+}
+
+// A leaf of the resource tree: type / name / language -> data
+#[derive(Debug, Clone)]
+pub struct PeResource<'a> {
+    pub kind: ResourceId,
+    pub name: ResourceId,
+    pub language: u16,
+    pub data: &'a [u8],
+}
+
+impl PeFile {
+    pub fn resources(&self) -> Result<Vec<PeResource<'_>>> {
+        let Some((rva, _)) = self.data_directory(RESOURCE_DIRECTORY) else {
+            return Ok(Vec::new());
+        };
+        let root = self
+            .rva_to_offset(rva)
+            .ok_or(anyhow!("Resource directory is outside every section"))?;
+
+        let mut resources = Vec::new();
+        let mut budget = MAX_DIRECTORY_ENTRIES;
+        let mut visited = HashSet::from([root]);
+        for (kind, types) in self.directory_entries(root, root, &mut budget)? {
+            let types = root + subdirectory(types)?;
+            if !visited.insert(types) {
+                continue;
+            }
+            for (name, names) in self.directory_entries(root, types, &mut budget)? {
+                let names = root + subdirectory(names)?;
+                if !visited.insert(names) {
+                    continue;
+                }
+                for (language, leaf) in self.directory_entries(root, names, &mut budget)? {
+                    if resources.len() == MAX_RESOURCES {
+                        return Ok(resources);
+                    }
+                    let ResourceId::Id(language) = language else {
+                        continue;
+                    };
+                    if leaf & 0x8000_0000 != 0 {
+                        continue;
+                    }
+                    let leaf = root + leaf as usize;
+                    let data_rva = read_u32(self.data(), leaf)?;
+                    let size = read_u32(self.data(), leaf + 4)? as usize;
+                    let Some(data) = self
+                        .rva_to_offset(data_rva)
+                        .and_then(|offset| self.data().get(offset..offset + size))
+                    else {
+                        continue;
+                    };
+                    resources.push(PeResource {
+                        kind: kind.clone(),
+                        name: name.clone(),
+                        language,
+                        data,
+                    });
+                }
+            }
+        }
+        Ok(resources)
+    }
+
+    pub fn resources_of_type(&self, kind: u16) -> Result<Vec<PeResource<'_>>> {
+        Ok(self
+            .resources()?
+            .into_iter()
+            .filter(|resource| resource.kind == ResourceId::Id(kind))
+            .collect())
+    }
+
+    // Reads at most `budget` entries and takes them off it
+    fn directory_entries(
+        &self,
+        root: usize,
+        directory: usize,
+        budget: &mut usize,
+    ) -> Result<Vec<(ResourceId, u32)>> {
+        let data = self.data();
+        let count = (usize::from(read_u16(data, directory + 12)?)
+            + usize::from(read_u16(data, directory + 14)?))
+        .min(*budget);
+        *budget -= count;
+
+        (0..count)
+            .map(|index| {
+                let entry = directory + 16 + index * 8;
+                let name = read_u32(data, entry)?;
+                let offset = read_u32(data, entry + 4)?;
+                let id = if name & 0x8000_0000 != 0 {
+                    let string = root + (name & 0x7FFF_FFFF) as usize;
+                    let length = usize::from(read_u16(data, string)?).min(MAX_NAME_LENGTH);
+                    let units: Vec<u16> = (0..length)
+                        .map(|unit| read_u16(data, string + 2 + unit * 2))
+                        .collect::<Result<_>>()?;
+                    ResourceId::Name(String::from_utf16_lossy(&units))
+                } else {
+                    ResourceId::Id(name as u16)
+                };
+                Ok((id, offset))
+            })
+            .collect()
+    }
+}
+
+// Directory entries pointing at another directory have their high bit set
+fn subdirectory(offset: u32) -> Result<usize> {
+    if offset & 0x8000_0000 == 0 {
+        return Err(anyhow!("Malformed resource directory"));
+    }
+    Ok((offset & 0x7FFF_FFFF) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SIGNED_FIXTURE;
+    use super::*;
+
+    #[test]
+    fn walks_the_resource_tree() {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        let resources = pe.resources().unwrap();
+        let leaves: Vec<(ResourceId, ResourceId, u16)> = resources
+            .iter()
+            .map(|resource| {
+                (
+                    resource.kind.clone(),
+                    resource.name.clone(),
+                    resource.language,
+                )
+            })
+            .collect();
+        assert_eq!(
+            leaves,
+            [
+                (ResourceId::Id(RT_ICON), ResourceId::Id(1), 0x409),
+                (ResourceId::Id(RT_GROUP_ICON), ResourceId::Id(1), 0x409),
+                (ResourceId::Id(RT_VERSION), ResourceId::Id(1), 0x409),
+            ]
+        );
+        assert_eq!(pe.resources_of_type(RT_VERSION).unwrap().len(), 1);
+        assert!(pe.resources_of_type(RT_MANIFEST).unwrap().is_empty());
+    }
+
+    #[test]
+    fn skips_directories_already_walked() {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        let (rva, _) = pe.data_directory(RESOURCE_DIRECTORY).unwrap();
+        let root = pe.rva_to_offset(rva).unwrap();
+
+        // Point the RT_ICON type entry back at the root directory
+        let mut data = SIGNED_FIXTURE.to_vec();
+        data[root + 20..root + 24].copy_from_slice(&0x8000_0000u32.to_le_bytes());
+        let pe = PeFile::parse(data).unwrap();
+        let kinds: Vec<ResourceId> = pe
+            .resources()
+            .unwrap()
+            .into_iter()
+            .map(|resource| resource.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [ResourceId::Id(RT_GROUP_ICON), ResourceId::Id(RT_VERSION)]
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_images() {
+        for length in [0, 2, 64, 200, 400] {
+            assert!(PeFile::parse(SIGNED_FIXTURE[..length].to_vec()).is_err());
+        }
+        // Headers intact but the resource section cut off
+        let pe = PeFile::parse(SIGNED_FIXTURE[..1100].to_vec()).unwrap();
+        let _ = pe.resources();
+    }
+}
@@ -0,0 +1,208 @@
+use super::{PeFile, RT_VERSION, read_u16, read_u32};
+use anyhow::{Result, anyhow};
+
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xFEEF_04BD;
+
+// One StringFileInfo table, keyed like `040904b0` (language then code page)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionStringTable {
+    pub key: String,
+    pub values: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionInfo {
+    // From VS_FIXEDFILEINFO, always numeric
+    pub file_version: Option<String>,
+    pub product_version: Option<String>,
+    pub tables: Vec<VersionStringTable>,
+}
+
+impl VersionInfo {
+    // First non-empty value for `key`, with US English tables ahead of the others
+    pub fn string(&self, key: &str) -> Option<&str> {
+        let mut tables: Vec<&VersionStringTable> = self.tables.iter().collect();
+        tables.sort_by_key(|table| !table.key.to_ascii_lowercase().starts_with("0409"));
+        tables.iter().find_map(|table| {
+            table
+                .values
+                .iter()
+                .find(|(name, value)| name.eq_ignore_ascii_case(key) && !value.trim().is_empty())
+                .map(|(_, value)| value.trim())
+        })
+    }
+}
+
+impl PeFile {
+    pub fn version_info(&self) -> Result<Option<VersionInfo>> {
+        match self.resources_of_type(RT_VERSION)?.first() {
+            Some(resource) => parse_version_info(resource.data).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+// A VS_VERSIONINFO-style block: length, value length, type, key, value, children
+struct Block<'a> {
+    key: String,
+    value: &'a [u8],
+    is_text: bool,
+    children: &'a [u8],
+    length: usize,
+}
+
+pub fn parse_version_info(data: &[u8]) -> Result<VersionInfo> {
+    let root = parse_block(data)?;
+    if root.key != "VS_VERSION_INFO" {
+        return Err(anyhow!("Not a version resource"));
+    }
+
+    let mut info = VersionInfo::default();
+    if root.value.len() >= 24 && read_u32(root.value, 0)? == FIXED_FILE_INFO_SIGNATURE {
+        info.file_version = Some(format_version(
+            read_u32(root.value, 8)?,
+            read_u32(root.value, 12)?,
+        ));
+        info.product_version = Some(format_version(
+            read_u32(root.value, 16)?,
+            read_u32(root.value, 20)?,
+        ));
+    }
+
+    for child in children(root.children) {
+        if child.key != "StringFileInfo" {
+            continue;
+        }
+        for table in children(child.children) {
+            let values = children(table.children)
+                .map(|string| {
+                    let value = if string.is_text {
+                        utf16_string(string.value)
+                    } else {
+                        String::from_utf8_lossy(string.value)
+                            .trim_end_matches('\0')
+                            .to_owned()
+                    };
+                    (string.key, value)
+                })
+                .collect();
+            info.tables.push(VersionStringTable {
+                key: table.key,
+                values,
+            });
+        }
+    }
+
+    Ok(info)
+}
+
+fn parse_block(data: &[u8]) -> Result<Block<'_>> {
+    let length = usize::from(read_u16(data, 0)?);
+    let value_length = usize::from(read_u16(data, 2)?);
+    let is_text = read_u16(data, 4)? == 1;
+    let data = data
+        .get(..length)
+        .ok_or(anyhow!("Version block runs past its resource"))?;
+
+    let mut offset = 6;
+    let mut key = Vec::new();
+    loop {
+        let unit = read_u16(data, offset)?;
+        offset += 2;
+        if unit == 0 {
+            break;
+        }
+        key.push(unit);
+    }
+    offset = align(offset);
+
+    // Text values count UTF-16 units, binary ones count bytes
+    let value_bytes = if is_text {
+        value_length * 2
+    } else {
+        value_length
+    };
+    let value = data
+        .get(offset..offset + value_bytes)
+        .unwrap_or(data.get(offset..).unwrap_or_default());
+    offset = align(offset + value_bytes);
+
+    Ok(Block {
+        key: String::from_utf16_lossy(&key),
+        value,
+        is_text,
+        children: data.get(offset..).unwrap_or_default(),
+        length,
+    })
+}
+
+// Siblings are packed back to back on 32-bit boundaries
+fn children(mut data: &[u8]) -> impl Iterator<Item = Block<'_>> {
+    std::iter::from_fn(move || {
+        let block = parse_block(data).ok().filter(|block| block.length > 0)?;
+        data = data.get(align(block.length)..).unwrap_or_default();
+        Some(block)
+    })
+}
+
+fn align(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+fn utf16_string(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .take_while(|unit| *unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn format_version(most_significant: u32, least_significant: u32) -> String {
+    format!(
+        "{}.{}.{}.{}",
+        most_significant >> 16,
+        most_significant & 0xFFFF,
+        least_significant >> 16,
+        least_significant & 0xFFFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SIGNED_FIXTURE;
+    use super::*;
+
+    fn fixture_resource() -> Vec<u8> {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        pe.resources_of_type(RT_VERSION).unwrap()[0].data.to_vec()
+    }
+
+    #[test]
+    fn parses_fixed_and_string_info() {
+        let pe = PeFile::parse(SIGNED_FIXTURE.to_vec()).unwrap();
+        let info = pe.version_info().unwrap().unwrap();
+        assert_eq!(info.file_version.as_deref(), Some("1.2.3.4"));
+        assert_eq!(info.product_version.as_deref(), Some("1.2.0.0"));
+        assert_eq!(info.tables.len(), 1);
+        assert_eq!(info.tables[0].key, "040904b0");
+        assert_eq!(info.string("ProductName"), Some("Fixture App"));
+        assert_eq!(info.string("productversion"), Some("1.2.0"));
+        assert_eq!(
+            info.string("LegalCopyright"),
+            Some("Copyright (C) AppPorter")
+        );
+        assert_eq!(info.string("InternalName"), None);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let data = fixture_resource();
+        assert!(parse_version_info(&data[..1]).is_err());
+        assert!(parse_version_info(&data[..40]).is_err());
+        // Cut inside the children, whatever parses must not panic
+        for length in 0..data.len() {
+            let _ = parse_version_info(&data[..length]);
+        }
+    }
+}
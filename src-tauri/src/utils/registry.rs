@@ -1,20 +1,69 @@
 use crate::configs::App;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::env;
-use windows_registry::{CURRENT_USER, LOCAL_MACHINE};
+use windows_registry::{CURRENT_USER, Key, LOCAL_MACHINE, Type};
 
-pub fn create_registry_entries(config: &App) -> Result<()> {
-    let key = if config.details.current_user_only {
-        CURRENT_USER.create(format!(
-            r"Software\Microsoft\Windows\CurrentVersion\Uninstall\{}",
-            config.details.info.name
-        ))?
+// A value of an uninstall key as its raw type and bytes, enough to write it back unchanged
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct RegistryValue {
+    pub name: String,
+    pub ty: u32,
+    pub data: Vec<u8>,
+}
+
+fn uninstall_key(app_name: &str, current_user_only: bool) -> (&'static Key, String) {
+    if current_user_only {
+        (
+            CURRENT_USER,
+            format!(r"Software\Microsoft\Windows\CurrentVersion\Uninstall\{app_name}"),
+        )
     } else {
-        LOCAL_MACHINE.create(format!(
-            r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\{}",
-            config.details.info.name
-        ))?
+        (
+            LOCAL_MACHINE,
+            format!(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\{app_name}"),
+        )
+    }
+}
+
+// Values of the uninstall key named after the app, `None` if there is no such key
+pub fn read_registry_entries(
+    app_name: &str,
+    current_user_only: bool,
+) -> Result<Option<Vec<RegistryValue>>> {
+    let (root, path) = uninstall_key(app_name, current_user_only);
+    let Ok(key) = root.open(&path) else {
+        return Ok(None);
     };
+    Ok(Some(
+        key.values()?
+            .map(|(name, value)| RegistryValue {
+                name,
+                ty: value.ty().into(),
+                data: value.to_vec(),
+            })
+            .collect(),
+    ))
+}
+
+// Replaces the uninstall key with the given values, as read by `read_registry_entries`
+pub fn restore_registry_entries(
+    app_name: &str,
+    current_user_only: bool,
+    values: &[RegistryValue],
+) -> Result<()> {
+    remove_registry_entries(app_name, current_user_only)?;
+    let (root, path) = uninstall_key(app_name, current_user_only);
+    let key = root.create(&path)?;
+    for value in values {
+        key.set_bytes(&value.name, Type::from(value.ty), &value.data)?;
+    }
+    Ok(())
+}
+
+pub fn create_registry_entries(config: &App) -> Result<()> {
+    let (root, path) = uninstall_key(&config.details.info.name, config.details.current_user_only);
+    let key = root.create(path)?;
 
     key.set_string("Comments", "Installed with AppPorter")?;
     key.set_string("DisplayIcon", &config.details.full_path)?;
@@ -37,18 +86,9 @@ pub fn create_registry_entries(config: &App) -> Result<()> {
 }
 
 pub fn remove_registry_entries(app_name: &str, current_user_only: bool) -> Result<()> {
-    let key;
-
-    if current_user_only {
-        key = format!(r"Software\Microsoft\Windows\CurrentVersion\Uninstall\{app_name}");
-        if CURRENT_USER.open(&key).is_ok() {
-            CURRENT_USER.remove_tree(&key)?;
-        }
-    } else {
-        key = format!(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall\{app_name}");
-        if LOCAL_MACHINE.open(&key).is_ok() {
-            LOCAL_MACHINE.remove_tree(&key)?;
-        }
+    let (root, path) = uninstall_key(app_name, current_user_only);
+    if root.open(&path).is_ok() {
+        root.remove_tree(&path)?;
     }
     Ok(())
 }
@@ -1,6 +1,10 @@
 import type { AppDetails } from '#/AppDetails'
+import type { AppInstallConfig } from '#/AppInstallConfig'
+import type { ConflictKind } from '#/ConflictKind'
+import type { ExtractionFilter } from '#/ExtractionFilter'
 import { FileTreeNode } from '#/FileTreeNode'
 import type { ToolDetails } from '#/ToolDetails'
+import type { ToolInstallConfig } from '#/ToolInstallConfig'
 import { defineStore } from 'pinia'
 
 interface InstallConfig {
@@ -22,6 +26,8 @@ interface InstallConfig {
   }
   archive_exe_path?: string
   archive_path_dir?: string
+  extraction_filter: ExtractionFilter
+  conflict_overrides: ConflictKind[]
 
   app_details: AppDetails
   tool_details: ToolDetails
@@ -45,6 +51,12 @@ export const InstallConfigStore = defineStore('install_config', {
       archive_password: '',
       file_tree: [],
     },
+    extraction_filter: {
+      root: '',
+      include: [],
+      exclude: [],
+    },
+    conflict_overrides: [],
 
     app_details: {
       current_user_only: false,
@@ -63,15 +75,86 @@ export const InstallConfigStore = defineStore('install_config', {
       },
       install_path: '',
       full_path: '',
+      unverified_signature: null,
+      filter: {
+        root: '',
+        include: [],
+        exclude: [],
+      },
     },
     tool_details: {
       name: '',
       add_to_path: [false, ''],
       install_path: '',
+      filter: {
+        root: '',
+        include: [],
+        exclude: [],
+      },
     },
   }),
 
   actions: {
+    appInstallConfig(): AppInstallConfig {
+      return {
+        app: {
+          id: this.id,
+          timestamp_add: '',
+          timestamp_update: '',
+          installed: false,
+          url: this.url,
+          archive_password: this.archive_password,
+          details: this.app_details,
+          validation_status: {
+            file_exists: false,
+            registry_valid: false,
+            path_exists: false,
+          },
+          update_status: {
+            update_available: false,
+            last_final_url: '',
+            last_etag: '',
+            last_modify: '',
+            last_check: '',
+          },
+        },
+        archive_exe_path: this.archive_exe_path ?? '',
+        archive_path_dir: this.app_details.config.add_to_path[1],
+        zip_path: this.zip_path,
+        filter: this.extraction_filter,
+        overrides: this.conflict_overrides,
+      }
+    },
+
+    toolInstallConfig(): ToolInstallConfig {
+      return {
+        tool: {
+          id: this.id,
+          timestamp_add: '',
+          timestamp_update: '',
+          installed: false,
+          url: this.url,
+          archive_password: this.archive_password,
+          details: this.tool_details,
+          validation_status: {
+            file_exists: false,
+            path_exists: false,
+          },
+          update_status: {
+            update_available: false,
+            last_final_url: '',
+            last_etag: '',
+            last_modify: '',
+            last_check: '',
+          },
+        },
+        archive_path_dir: this.tool_details.add_to_path[1],
+        zip_path: this.zip_path,
+        filter: this.extraction_filter,
+        overrides: this.conflict_overrides,
+      }
+    },
+
     setTempData(data: {
       id?: string
       zip_path?: string
@@ -36,6 +36,15 @@ export const SettingsStore = defineStore('settings', {
       install_path: '',
       add_to_path: false,
     },
+    archive: {
+      unwrap_depth: 3,
+      test_before_install: false,
+      extraction_policy: {
+        max_path_length: 260,
+        report_only: [],
+      },
+      cache_size_limit_mb: 4096,
+    },
     unlistenThemeColor: null,
   }),
 
@@ -50,6 +50,7 @@ const USED_ICONS = [
   'reset_image',
   'edit',
   'update',
+  'lock',
 ] as const
 
 /**